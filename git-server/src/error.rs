@@ -39,6 +39,10 @@ pub enum Error {
     #[error("unauthorized: {0}")]
     Unauthorized(&'static str),
 
+    /// Authentication token error.
+    #[error("unauthorized: {0}")]
+    Auth(#[from] shared::auth::Error),
+
     /// Post-receive hook error.
     #[error("{0}")]
     PostReceive(&'static str),
@@ -47,6 +51,10 @@ pub enum Error {
     #[error("alias does not exist")]
     AliasNotFound,
 
+    /// Project not found, or denied by the seed's policy.
+    #[error("project does not exist")]
+    ProjectNotFound,
+
     /// Id is not valid.
     #[error("id is not valid")]
     InvalidId,
@@ -112,6 +120,10 @@ pub enum Error {
     #[error("error verifying identity: {0}")]
     VerifyIdentity(String),
 
+    /// An error occured with the project index.
+    #[error(transparent)]
+    ProjectIndex(#[from] project_index::Error),
+
     /// An error occured with a git storage pool.
     #[error(transparent)]
     Pool(#[from] librad::git::storage::pool::PoolError),
@@ -134,8 +146,8 @@ impl Error {
         match self {
             Error::UnsupportedContentEncoding(_) => http::StatusCode::NOT_IMPLEMENTED,
             Error::ServiceUnavailable(_) => http::StatusCode::SERVICE_UNAVAILABLE,
-            Error::Unauthorized(_) => http::StatusCode::UNAUTHORIZED,
-            Error::AliasNotFound => http::StatusCode::NOT_FOUND,
+            Error::Unauthorized(_) | Error::Auth(_) => http::StatusCode::UNAUTHORIZED,
+            Error::AliasNotFound | Error::ProjectNotFound => http::StatusCode::NOT_FOUND,
             _ => http::StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
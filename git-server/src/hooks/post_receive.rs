@@ -25,6 +25,7 @@ use librad::git::Urn;
 use librad::paths::Paths;
 use librad::profile::Profile;
 use librad::PeerId;
+use shared::issues::ISSUES_PREFIX;
 
 use super::storage::Storage;
 use super::{types::ReceivePackEnv, CertSignerDetails};
@@ -144,6 +145,12 @@ impl PostReceive {
 
                 println!("Updating ref for {}: {}", peer_id, rest);
 
+                // Issues are accepted from any authorized pusher, and were verified in the
+                // `pre-receive` hook.
+                if let Some(id) = rest.strip_prefix(ISSUES_PREFIX) {
+                    println!("Issue {} updated by {}", id, peer_id);
+                    continue;
+                }
                 // Only delegates can update refs.
                 if !self.delegates.contains(&peer_id) {
                     continue;
@@ -157,7 +164,7 @@ impl PostReceive {
                 // TODO: This should only update when a quorum is reached between delegates.
                 // For a single delegate, we can just always allow it.
                 if self.delegates.len() == 1 {
-                    self.set_head(refname.as_str(), default_branch, repo)?;
+                    self.set_head(&peer_id, refname.as_str(), default_branch, repo)?;
                 } else {
                     println!("Cannot set head for multi-delegate project: not supported.");
                 }
@@ -178,6 +185,7 @@ impl PostReceive {
     /// branch.
     fn set_head(
         &self,
+        peer_id: &PeerId,
         branch_ref: &str,
         branch: &str,
         repo: &Repository,
@@ -200,10 +208,21 @@ impl PostReceive {
         let local_branch_ref = &format!("{}/refs/heads/{}", namespace_path, branch);
 
         println!("Setting ref {:?} -> {:?}", &local_branch_ref, oid);
-        repo.reference(local_branch_ref, oid, true, "set-local-branch (radicle)")?;
+        // The peer is recorded in the reflog messages, to be read back as activity.
+        repo.reference(
+            local_branch_ref,
+            oid,
+            true,
+            &format!("set-local-branch (radicle) {}", peer_id),
+        )?;
 
         println!("Setting ref {:?} -> {:?}", head_ref, local_branch_ref);
-        repo.reference_symbolic(&head_ref, local_branch_ref, true, "set-head (radicle)")?;
+        repo.reference_symbolic(
+            &head_ref,
+            local_branch_ref,
+            true,
+            &format!("set-head (radicle) {}", peer_id),
+        )?;
 
         Ok(oid)
     }
@@ -212,7 +231,7 @@ impl PostReceive {
         eprintln!("Verifying identity...");
 
         // Make sure one of the ref updates is initializing `rad/id`.
-        let (_, _, identity_oid) = if let Some(update) = self
+        let (identity_ref, _, identity_oid) = if let Some(update) = self
             .updates
             .iter()
             .find(|(refname, _, _)| refname.ends_with(RAD_ID_REF))
@@ -266,11 +285,15 @@ impl PostReceive {
         }
 
         // Set local project identity to point to the verified commit pushed by the user.
+        let message = match crate::parse_ref(identity_ref) {
+            Ok((peer_id, _)) => format!("set-project-id ({}) {}", self.key_fingerprint, peer_id),
+            Err(_) => format!("set-project-id ({})", self.key_fingerprint),
+        };
         repo.reference(
             &self.namespace_ref(RAD_ID_REF),
             *identity_oid,
             false,
-            &format!("set-project-id ({})", self.key_fingerprint),
+            &message,
         )?;
 
         Ok(())
@@ -16,14 +16,17 @@
 //!
 //! The `pre-receive` git hook provides access to GPG certificates for a signed push, useful for authorizing an
 //! update the repository.
-use std::io;
+//!
+//! Issues pushed under `refs/remotes/<peer>/cobs/issues/<id>` are also checked for well-formedness
+//! here, since they can no longer be rejected once the refs are updated.
 use std::io::prelude::*;
 use std::io::stdin;
 use std::str::FromStr;
 
 use envconfig::Envconfig;
 use git2::{Oid, Repository};
-use librad::PeerId;
+use shared::issues::{CHANGE_BLOB, ISSUES_PREFIX};
+use shared::ssh;
 
 use super::{
     types::{CertNonceStatus, CertStatus, ReceivePackEnv},
@@ -101,6 +104,7 @@ impl PreReceive {
         pre_receive.verify_certificate()?;
         pre_receive.check_authorized_key()?;
         pre_receive.authorize_ref_updates()?;
+        pre_receive.verify_issue_updates()?;
 
         Ok(())
     }
@@ -109,12 +113,10 @@ impl PreReceive {
     /// key as the owner/parent of the ref.
     fn authorize_ref_updates(&self) -> Result<(), Error> {
         // This is the fingerprint of the key used to sign the push certificate.
-        let key_fingerprint = self
-            .key_fingerprint
-            .strip_prefix("SHA256:")
-            .ok_or(Error::Unauthorized("key fingerprint is not a SHA-256 hash"))?;
-        let key_fingerprint = base64::decode(key_fingerprint)
-            .map_err(|_| Error::Unauthorized("key fingerprint is not valid"))?;
+        if !self.key_fingerprint.starts_with("SHA256:") {
+            return Err(Error::Unauthorized("key fingerprint is not a SHA-256 hash"));
+        }
+        let key_fingerprint = self.key_fingerprint.trim_end_matches('=');
 
         // We iterate over each ref update and make sure they are all authorized. We need
         // to check that updates are only done to refs under `<project>/refs/remotes/<peer>`
@@ -124,15 +126,79 @@ impl PreReceive {
             // key fingerpint.
             let (peer_id, _) = crate::parse_ref(refname)
                 .map_err(|_| Error::InvalidRefPushed(refname.to_owned()))?;
-            let peer_fingerprint = to_ssh_fingerprint(&peer_id)?;
+            let peer_fingerprint = shared::ssh::peer_fingerprint(&peer_id);
 
-            if key_fingerprint[..] != peer_fingerprint[..] {
+            if key_fingerprint != peer_fingerprint {
                 return Err(Error::Unauthorized("signer does not match remote ref"));
             }
         }
         Ok(())
     }
 
+    /// Verifies updates to issue refs.
+    ///
+    /// An issue is a graph of signed commits rooted at the commit whose oid is the issue id,
+    /// each holding a single change in a `change.json` blob. Changes that aren't published
+    /// under the issue yet must be signed by the pushing peer, whose key signed the push
+    /// certificate, see [`PreReceive::authorize_ref_updates`].
+    fn verify_issue_updates(&self) -> Result<(), Error> {
+        // Pushed objects are kept in a quarantine directory until the hook succeeds, which
+        // is only visible through the environment.
+        let repo = Repository::open_from_env()?;
+
+        for (refname, old, new) in self.updates.iter() {
+            let (peer_id, rest) = crate::parse_ref(refname)
+                .map_err(|_| Error::InvalidRefPushed(refname.to_owned()))?;
+            let id = match rest.strip_prefix(ISSUES_PREFIX) {
+                Some(id) => {
+                    Oid::from_str(id).map_err(|_| Error::InvalidRefPushed(refname.to_owned()))?
+                }
+                None => continue,
+            };
+            // Deletions.
+            if new.is_zero() {
+                continue;
+            }
+            eprintln!("Verifying issue {}...", id);
+
+            let fingerprint = ssh::peer_fingerprint(&peer_id);
+            let mut walk = repo.revwalk()?;
+            walk.push(*new)?;
+            if !old.is_zero() {
+                walk.hide(*old)?;
+            }
+            // Changes already published by any peer were verified when they were pushed. Refs
+            // are relative to the pushed namespace, which the repository is opened with.
+            walk.hide_glob(&format!("refs/remotes/*/{}{}", ISSUES_PREFIX, id))?;
+            for oid in walk {
+                let oid = oid?;
+                let commit = repo.find_commit(oid)?;
+
+                if commit.parent_count() == 0 && oid != id {
+                    return Err(Error::InvalidRefPushed(refname.to_owned()));
+                }
+                if commit.tree()?.get_name(CHANGE_BLOB).is_none() {
+                    return Err(Error::InvalidRefPushed(refname.to_owned()));
+                }
+                let (signature, data) = repo
+                    .extract_signature(&oid, None)
+                    .map_err(|_| Error::Unauthorized("issue changes must be signed"))?;
+                let signature = ssh::Signature::from_armored(&String::from_utf8_lossy(&signature))
+                    .ok_or(Error::Unauthorized("issue changes must be signed with SSH"))?;
+
+                if signature.fingerprint() != fingerprint {
+                    return Err(Error::Unauthorized(
+                        "issue change is not signed by the pusher",
+                    ));
+                }
+                if signature.verify(&data) != ssh::Status::Valid {
+                    return Err(Error::Unauthorized("invalid issue change signature"));
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// This method will succeed iff the cert status is "OK"
     fn verify_certificate(&self) -> Result<(), Error> {
         eprintln!("Verifying certificate...");
@@ -186,20 +252,3 @@ impl PreReceive {
         Err(Error::Unauthorized("key is not authorized to push"))
     }
 }
-
-/// Get the SSH key fingerprint from a peer id.
-fn to_ssh_fingerprint(peer_id: &PeerId) -> Result<Vec<u8>, io::Error> {
-    use byteorder::{BigEndian, WriteBytesExt};
-    use sha2::Digest;
-
-    let mut buf = Vec::new();
-    let name = b"ssh-ed25519";
-    let key = peer_id.as_public_key().as_ref();
-
-    buf.write_u32::<BigEndian>(name.len() as u32)?;
-    buf.extend_from_slice(name);
-    buf.write_u32::<BigEndian>(key.len() as u32)?;
-    buf.extend_from_slice(key);
-
-    Ok(sha2::Sha256::digest(&buf).to_vec())
-}
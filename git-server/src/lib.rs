@@ -11,22 +11,17 @@ use std::fs::File;
 use std::io::prelude::*;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::sync::Arc;
 use std::{io, net};
 
 use anyhow::bail;
 use anyhow::Context as _;
-use either::Either;
 use flate2::write::GzDecoder;
 use http::{HeaderMap, Method};
-use librad::git::identities;
-use librad::git::storage::Pool;
-use librad::git::{self, Urn};
-use librad::identities::SomeIdentity;
+use librad::git::storage::ReadOnly;
+use librad::git::Urn;
 use librad::paths::Paths;
 use librad::profile::Profile;
 use librad::PeerId;
-use tokio::sync::RwLock;
 use warp::hyper::StatusCode;
 use warp::reply;
 use warp::{self, path, Buf, Filter, Rejection, Reply};
@@ -34,7 +29,6 @@ use warp::{self, path, Buf, Filter, Rejection, Reply};
 use error::Error;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
-pub const STORAGE_POOL_SIZE: usize = 3;
 pub const AUTHORIZED_KEYS_FILE: &str = "authorized-keys";
 pub const POST_RECEIVE_OK_HOOK: &str = "post-receive-ok";
 
@@ -47,6 +41,8 @@ pub struct Options {
     pub git_receive_pack: bool,
     pub cert_nonce_seed: Option<String>,
     pub allow_unauthorized_keys: bool,
+    pub alias_file: Option<PathBuf>,
+    pub policy_file: Option<PathBuf>,
 }
 
 #[derive(Clone)]
@@ -57,8 +53,10 @@ pub struct Context {
     cert_nonce_seed: Option<String>,
     git_receive_hook: PathBuf,
     allow_unauthorized_keys: bool,
-    aliases: Arc<RwLock<HashMap<String, Urn>>>,
-    pool: Pool<git::storage::ReadOnly>,
+    /// Index of projects and persons, shared with the other services.
+    projects: project_index::Index,
+    /// Peer id of the seed, which authentication tokens are bound to.
+    peer_id: PeerId,
 }
 
 impl Context {
@@ -69,11 +67,15 @@ impl Context {
             Profile::load()?.paths().clone()
         };
 
-        let pool = git::storage::Pool::new(
-            git::storage::pool::ReadConfig::new(paths.clone()),
-            STORAGE_POOL_SIZE,
+        let projects = project_index::Index::new(
+            paths.clone(),
+            project_index::Config {
+                alias_file: options.alias_file.clone(),
+                policy_file: options.policy_file.clone(),
+            },
         );
 
+        let peer_id = ReadOnly::open(&paths)?.peer_id().to_owned();
         let git_root = paths.git_dir().canonicalize()?;
         let git_receive_hook = git_root.join("hooks").join(POST_RECEIVE_OK_HOOK);
 
@@ -86,8 +88,8 @@ impl Context {
             git_receive_hook,
             cert_nonce_seed: options.cert_nonce_seed.clone(),
             allow_unauthorized_keys: options.allow_unauthorized_keys,
-            aliases: Default::default(),
-            pool,
+            projects,
+            peer_id,
         })
     }
 
@@ -175,6 +177,17 @@ impl Context {
         Ok(())
     }
 
+    /// Keeps reflogs for all refs, including namespaced refs, which git otherwise doesn't log
+    /// in bare repositories. The http-api reads project activity from these reflogs.
+    pub fn enable_reflogs(&self) -> Result<(), Error> {
+        let field = "core.logAllRefUpdates";
+        let value = "always";
+
+        self.set_root_git_config(field, value)?;
+
+        Ok(())
+    }
+
     /// Updates the git config in the root project.
     pub fn set_root_git_config(&self, field: &str, value: &str) -> Result<(), Error> {
         let path = self.paths.git_dir().join("config");
@@ -188,75 +201,46 @@ impl Context {
         Ok(())
     }
 
-    /// Populates alias map with unique projects' names and their urns
-    async fn populate_aliases(&self, map: &mut HashMap<String, Urn>) -> Result<(), Error> {
-        use librad::git::identities::SomeIdentity::Project;
-
-        let storage = self.pool.get().await?;
-        let identities = identities::any::list(&storage)?;
-
-        for identity in identities.flatten() {
-            if let Project(project) = identity {
-                let urn = project.urn();
-                let name = (&project.payload().subject.name).to_string();
+    /// Authenticate the peer making a request, given a token in the `Authorization` header,
+    /// either as `Bearer <token>`, or as the password of HTTP basic authentication, which is
+    /// what git sends. See [`shared::auth`].
+    fn authenticate(&self, headers: &HeaderMap) -> Result<PeerId, Error> {
+        let header = headers
+            .get(http::header::AUTHORIZATION)
+            .ok_or(Error::Unauthorized("missing credentials"))?
+            .to_str()
+            .map_err(|_| Error::Unauthorized("invalid credentials"))?;
+
+        let token = if let Some(token) = header.strip_prefix("Bearer ") {
+            token.to_owned()
+        } else if let Some(credentials) = header.strip_prefix("Basic ") {
+            let credentials = base64::decode(credentials)
+                .ok()
+                .and_then(|c| String::from_utf8(c).ok())
+                .ok_or(Error::Unauthorized("invalid credentials"))?;
+
+            // The user name is ignored, since the token identifies the peer.
+            credentials
+                .split_once(':')
+                .map(|(_, password)| password.to_owned())
+                .ok_or(Error::Unauthorized("invalid credentials"))?
+        } else {
+            return Err(Error::Unauthorized("unsupported authorization scheme"));
+        };
+        Ok(shared::auth::verify(&token, &self.peer_id)?)
+    }
 
-                tracing::info!("alias {:?} for {:?}", name, urn.to_string());
+    /// Get the name, delegates and default branch of a project or person.
+    fn get_meta(&self, urn: &Urn) -> Result<(Option<String>, Vec<PeerId>, Option<String>), Error> {
+        if let Some(project) = self.projects.project(urn) {
+            let default_branch = project.default_branch.ok_or(Error::NoDefaultBranch)?;
 
-                if let std::collections::hash_map::Entry::Vacant(e) = map.entry(name.clone()) {
-                    e.insert(urn);
-                } else {
-                    tracing::warn!("alias {:?} exists, skipping", name);
-                }
-            }
+            return Ok((Some(project.name), project.delegates, Some(default_branch)));
         }
-
-        Ok(())
-    }
-
-    async fn get_meta(
-        &self,
-        urn: &Urn,
-    ) -> Result<(Option<String>, Vec<PeerId>, Option<String>), Error> {
-        let storage = self.pool.get().await?;
-        let doc = identities::any::get(&storage, urn)?;
-
-        if let Some(doc) = doc {
-            let mut peer_ids = Vec::new();
-            let mut default_branch = None;
-            let mut name = None;
-
-            match doc {
-                SomeIdentity::Person(doc) => {
-                    name = Some(doc.payload().subject.name.to_string());
-                    peer_ids.extend(doc.delegations().iter().cloned().map(PeerId::from))
-                }
-                SomeIdentity::Project(doc) => {
-                    name = Some(doc.payload().subject.name.to_string());
-                    default_branch = Some(
-                        doc.subject()
-                            .default_branch
-                            .clone()
-                            .ok_or(Error::NoDefaultBranch)?
-                            .to_string(),
-                    );
-
-                    for delegation in doc.delegations() {
-                        match delegation {
-                            Either::Left(pk) => peer_ids.push(PeerId::from(*pk)),
-                            Either::Right(indirect) => {
-                                peer_ids.extend(
-                                    indirect.delegations().iter().cloned().map(PeerId::from),
-                                );
-                            }
-                        }
-                    }
-                }
-                _ => {}
-            }
-            Ok((name, peer_ids, default_branch))
-        } else {
-            Ok((None, vec![], None))
+        if let Some(person) = self.projects.person(urn) {
+            return Ok((Some(person.name), person.delegates, None));
         }
+        Ok((None, vec![], None))
     }
 }
 
@@ -270,12 +254,9 @@ pub async fn run(options: Options) -> anyhow::Result<()> {
     tracing::info!("{}", std::str::from_utf8(&git_version)?.trim());
 
     let ctx = Context::from(&options).expect("context creation must not fail");
-    {
-        let mut aliases = ctx.aliases.write().await;
-
-        ctx.populate_aliases(&mut aliases)
-            .await
-            .context("populating aliases")?;
+    ctx.projects.refresh().context("building project index")?;
+    if let Err(e) = ctx.projects.watch() {
+        tracing::warn!("Project index won't be refreshed on changes: {}", e);
     }
 
     if let Err(e) = ctx.set_cert_nonce_seed() {
@@ -290,6 +271,12 @@ pub async fn run(options: Options) -> anyhow::Result<()> {
     if let Err(e) = ctx.disable_signers_file() {
         bail!("Failed to set signers file config: {:?}", e);
     }
+    if let Err(e) = ctx.enable_reflogs() {
+        tracing::warn!(
+            "Failed to set reflog config, project activity will be incomplete: {:?}",
+            e
+        );
+    }
 
     let peer_id = path::param::<PeerId>()
         .map(Some)
@@ -345,12 +332,10 @@ async fn git_handler(
         } else {
             tracing::debug!("looking for project alias {:?}", name);
 
-            let mut aliases = ctx.aliases.write().await;
-            if !aliases.contains_key(name) {
-                // If the alias does not exist, rebuild the cache.
-                ctx.populate_aliases(&mut aliases).await?;
-            }
-            let urn = aliases.get(name).cloned().ok_or(Error::AliasNotFound)?;
+            let urn = ctx
+                .projects
+                .resolve_alias(name)
+                .ok_or(Error::AliasNotFound)?;
             tracing::debug!("project alias resolved to {}", urn);
 
             urn
@@ -358,6 +343,12 @@ async fn git_handler(
     } else {
         Urn::try_from_id(project_id).map_err(|_| Error::InvalidId)?
     };
+    // Denied projects can neither be fetched nor pushed to. Delegate rules don't apply to
+    // projects that aren't indexed yet, eg. on their first push: the index is kept up to date
+    // by watching the monorepo, so that requests for unknown URNs don't trigger refreshes.
+    if !ctx.projects.allows(&urn) {
+        return Err(Error::ProjectNotFound.into());
+    }
 
     let (status, headers, body) = git(
         ctx,
@@ -412,8 +403,17 @@ async fn git(
         }
         _ => vec![],
     };
+    // Private projects are only served to their readers. This applies to pushes as well,
+    // since the refs of the project are advertised to the pusher.
+    if ctx.projects.is_private(&urn) {
+        let reader = ctx.authenticate(&headers)?;
+
+        if !ctx.projects.can_read(&urn, Some(&reader)) {
+            return Err(Error::ProjectNotFound);
+        }
+    }
 
-    let (name, delegates, default_branch) = ctx.get_meta(&urn).await?;
+    let (name, delegates, default_branch) = ctx.get_meta(&urn)?;
 
     tracing::debug!("headers: {:?}", headers);
     tracing::debug!("namespace: {}", namespace);
@@ -580,8 +580,17 @@ async fn recover(err: Rejection) -> Result<Box<dyn Reply>, Infallible> {
     } else {
         StatusCode::BAD_REQUEST
     };
-
-    Ok(Box::new(reply::with_status(String::default(), status)))
+    let reply = reply::with_status(String::default(), status);
+
+    // Have git prompt for credentials, see [`Context::authenticate`].
+    if status == StatusCode::UNAUTHORIZED {
+        return Ok(Box::new(reply::with_header(
+            reply,
+            "WWW-Authenticate",
+            "Basic realm=\"radicle\"",
+        )));
+    }
+    Ok(Box::new(reply))
 }
 
 /// Helper method to generate random string for cert nonce;
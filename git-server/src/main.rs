@@ -41,6 +41,15 @@ pub struct Options {
     /// allow unauthorized keys, ignores gpg certificate verification
     #[argh(switch)]
     pub allow_unauthorized_keys: bool,
+
+    /// file of project aliases, one "<alias> <urn>" per line, taking precedence over names
+    #[argh(option)]
+    pub alias_file: Option<PathBuf>,
+
+    /// file of rules deciding which projects are served and to whom, eg. "deny delegate
+    /// <peer-id>" or "read <urn> <peer-id>"; denied projects are treated as not found
+    #[argh(option)]
+    pub policy_file: Option<PathBuf>,
 }
 
 impl Options {
@@ -59,6 +68,8 @@ impl From<Options> for server::Options {
             git_receive_pack: other.git_receive_pack,
             cert_nonce_seed: other.cert_nonce_seed,
             allow_unauthorized_keys: other.allow_unauthorized_keys,
+            alias_file: other.alias_file,
+            policy_file: other.policy_file,
         }
     }
 }
@@ -0,0 +1,137 @@
+//! Project activity, as recorded in namespace reflogs.
+//!
+//! The git-server hooks and org-node write reflog entries of the form
+//! `<action> (<origin>) [<peer>]`, eg. `set-local-branch (radicle) hyn…` when a delegate's push
+//! updates a local branch, or `set-project-id (<fingerprint>) [<peer>]` when an identity is
+//! initialized. Older entries don't record the peer.
+//!
+//! Note that git only keeps reflogs for refs outside of `refs/heads` and `refs/remotes`, such as
+//! namespaced refs, if `core.logAllRefUpdates` is set to `always` in the monorepo, which the
+//! git-server and org-node do on startup. Updates made before that aren't recorded.
+use radicle_daemon::{PeerId, Urn};
+use serde::Serialize;
+
+use crate::project::string;
+
+/// Kind of activity event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Kind {
+    /// A branch was updated following a push to the git-server.
+    Push,
+    /// A branch was updated following replication by org-node.
+    ReplicatedUpdate,
+    /// A project identity was initialized by a push.
+    #[serde(rename_all = "camelCase")]
+    IdentityInitialized {
+        /// SSH key fingerprint of the pusher.
+        fingerprint: String,
+    },
+    /// Any other ref update.
+    Other { message: String },
+}
+
+/// An activity event, read from a reflog entry.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    /// Project the ref belongs to.
+    pub urn: Urn,
+    #[serde(flatten)]
+    pub kind: Kind,
+    /// Ref name, relative to the namespace, eg. `refs/heads/master`.
+    pub refname: String,
+    /// Peer that caused the update, if known.
+    pub peer: Option<PeerId>,
+    /// Target before the update. Zero if the ref was created.
+    #[serde(with = "string")]
+    pub old: git2::Oid,
+    /// Target after the update.
+    #[serde(with = "string")]
+    pub new: git2::Oid,
+    /// Time of the update, in seconds since epoch.
+    pub timestamp: i64,
+}
+
+impl Event {
+    /// Parse a reflog entry. Returns `None` for entries that don't carry any activity of their
+    /// own, such as `HEAD` being pointed at the default branch.
+    pub fn parse(urn: &Urn, refname: &str, entry: &git2::ReflogEntry) -> Option<Self> {
+        let message = entry.message().unwrap_or_default();
+        let (action, origin, peer) = match message.split_once(" (") {
+            Some((action, rest)) => match rest.split_once(')') {
+                Some((origin, peer)) => (
+                    action,
+                    origin,
+                    PeerId::from_default_encoding(peer.trim()).ok(),
+                ),
+                None => (message, "", None),
+            },
+            None => (message, "", None),
+        };
+        let kind = match (action, origin) {
+            ("set-head", _) => return None,
+            ("set-local-branch", "radicle") => Kind::Push,
+            ("set-local-branch", "org-node") => Kind::ReplicatedUpdate,
+            ("set-project-id", fingerprint) => Kind::IdentityInitialized {
+                fingerprint: fingerprint.to_owned(),
+            },
+            _ => Kind::Other {
+                message: message.to_owned(),
+            },
+        };
+
+        Some(Self {
+            urn: urn.clone(),
+            kind,
+            refname: refname.to_owned(),
+            peer,
+            old: entry.id_old(),
+            new: entry.id_new(),
+            timestamp: entry.committer().when().seconds(),
+        })
+    }
+
+    /// Branch name, if the updated ref is a local branch.
+    pub fn branch(&self) -> Option<&str> {
+        self.refname.strip_prefix("refs/heads/")
+    }
+}
+
+/// Get the activity of a project, from the reflogs of its local branches and identity,
+/// most recent first. If a limit is given, only the most recent events are read.
+pub fn events(
+    repo: &git2::Repository,
+    urn: &Urn,
+    limit: Option<usize>,
+) -> Result<Vec<Event>, git2::Error> {
+    let namespace = format!("refs/namespaces/{}/", urn.encode_id());
+    let mut events = Vec::new();
+    let mut refs = Vec::new();
+
+    for reference in repo.references_glob(&format!("{}refs/heads/*", namespace))? {
+        if let Some(name) = reference?.name() {
+            refs.push(name.to_owned());
+        }
+    }
+    refs.push(format!("{}refs/rad/id", namespace));
+
+    for name in refs {
+        let refname = match name.strip_prefix(&namespace) {
+            Some(refname) => refname,
+            None => continue,
+        };
+        // Reflogs are read newest first.
+        let reflog = repo.reflog(&name)?;
+        let entries = reflog
+            .iter()
+            .filter_map(|entry| Event::parse(urn, refname, &entry))
+            .take(limit.unwrap_or(usize::MAX));
+
+        events.extend(entries);
+    }
+    events.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+    events.truncate(limit.unwrap_or(usize::MAX));
+
+    Ok(events)
+}
@@ -0,0 +1,92 @@
+//! SVG status badges, in the style of <https://shields.io>.
+use serde::Deserialize;
+
+use crate::feed::escape;
+
+/// Value shown on a badge.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Value {
+    /// Date of the latest commit on the default branch.
+    #[default]
+    Commit,
+    /// Number of project delegates.
+    Delegates,
+    /// Number of tracked peers.
+    Peers,
+    /// Latest tag.
+    Tag,
+}
+
+impl Value {
+    /// Label shown when none is given.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Commit => "last commit",
+            Self::Delegates => "delegates",
+            Self::Peers => "peers",
+            Self::Tag => "tag",
+        }
+    }
+}
+
+/// Label background color.
+const LABEL_COLOR: &str = "#555";
+/// Value background color.
+const VALUE_COLOR: &str = "#5b3ddb";
+
+/// Render a badge.
+pub fn render(label: &str, value: &str) -> String {
+    let label_width = width(label);
+    let value_width = width(value);
+    let total = label_width + value_width;
+    let (label, value) = (escape(label), escape(value));
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{total}" height="20" role="img" aria-label="{label}: {value}">
+  <title>{label}: {value}</title>
+  <linearGradient id="s" x2="0" y2="100%">
+    <stop offset="0" stop-color="#bbb" stop-opacity=".1"/>
+    <stop offset="1" stop-opacity=".1"/>
+  </linearGradient>
+  <clipPath id="r"><rect width="{total}" height="20" rx="3" fill="#fff"/></clipPath>
+  <g clip-path="url(#r)">
+    <rect width="{label_width}" height="20" fill="{label_color}"/>
+    <rect x="{label_width}" width="{value_width}" height="20" fill="{value_color}"/>
+    <rect width="{total}" height="20" fill="url(#s)"/>
+  </g>
+  <g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">
+    <text x="{label_x}" y="15" fill="#010101" fill-opacity=".3">{label}</text>
+    <text x="{label_x}" y="14">{label}</text>
+    <text x="{value_x}" y="15" fill="#010101" fill-opacity=".3">{value}</text>
+    <text x="{value_x}" y="14">{value}</text>
+  </g>
+</svg>
+"##,
+        total = total,
+        label = label,
+        value = value,
+        label_width = label_width,
+        value_width = value_width,
+        label_color = LABEL_COLOR,
+        value_color = VALUE_COLOR,
+        label_x = label_width as f32 / 2.,
+        value_x = label_width as f32 + value_width as f32 / 2.,
+    )
+}
+
+/// Approximate width of a badge section, in pixels, for 11px Verdana text with padding.
+fn width(text: &str) -> usize {
+    let text = text
+        .chars()
+        .map(|c| match c {
+            'i' | 'j' | 'l' | 'I' | '.' | ',' | ':' | ';' | '|' | '!' | '\'' => 4,
+            'f' | 'r' | 't' | ' ' | '-' | '(' | ')' | '[' | ']' | '/' => 5,
+            'm' | 'w' | 'M' | 'W' | '@' | '%' => 10,
+            c if c.is_ascii_uppercase() => 8,
+            _ => 7,
+        })
+        .sum::<usize>();
+
+    text + 10
+}
@@ -0,0 +1,186 @@
+//! Canonical project head, computed from the delegates' default branches.
+use std::collections::{BTreeSet, HashMap};
+
+use radicle_daemon::PeerId;
+use serde::Serialize;
+
+use crate::project::{string, Delegate};
+
+/// Rule by which the canonical head was decided.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Rule {
+    /// All delegate heads are on a single line of history: the most advanced head that a
+    /// quorum of delegates have in their history is canonical.
+    FastForward,
+    /// Delegate heads diverge: the canonical head is the most recent commit that a quorum of
+    /// delegates have in their history.
+    Quorum,
+}
+
+/// How far a delegate's head is from the canonical head.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Divergence {
+    /// Commits in the delegate's head that are not in the canonical head.
+    pub ahead: usize,
+    /// Commits in the canonical head that are not in the delegate's head.
+    pub behind: usize,
+}
+
+/// Canonical head of a project.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Canonical {
+    /// Canonical head commit.
+    #[serde(with = "string")]
+    pub head: git2::Oid,
+    /// Rule that decided the head.
+    pub rule: Rule,
+    /// Number of delegates needed for a quorum.
+    pub quorum: usize,
+    /// Divergence of each delegate peer from the canonical head.
+    pub divergence: HashMap<PeerId, Divergence>,
+}
+
+impl Canonical {
+    /// Compute the canonical head given the delegates and their peers' heads.
+    ///
+    /// The quorum defaults to a majority of delegates. A delegate with multiple peers, ie. a
+    /// person, counts once, and has a commit in its history if any of its peers do.
+    /// Returns `None` if no commit is agreed upon.
+    pub fn compute(
+        repo: &git2::Repository,
+        delegates: &[Delegate],
+        heads: &HashMap<PeerId, git2::Oid>,
+        quorum: Option<usize>,
+    ) -> Result<Option<Self>, git2::Error> {
+        let quorum = quorum.unwrap_or(delegates.len() / 2 + 1).max(1);
+        let tips = heads.values().cloned().collect::<BTreeSet<_>>();
+
+        if tips.is_empty() {
+            return Ok(None);
+        }
+
+        let head = if is_linear(repo, &tips)? {
+            // Tips are on a single line of history, so the most advanced tip with enough
+            // votes is in the history of all the others that have enough votes.
+            let mut head = None;
+            for tip in tips.iter() {
+                if votes(repo, delegates, heads, *tip)? < quorum {
+                    continue;
+                }
+                match head {
+                    Some(current) if !contains(repo, *tip, current)? => {}
+                    _ => head = Some(*tip),
+                }
+            }
+            head.map(|tip| (tip, Rule::FastForward))
+        } else {
+            // Candidates are the heads themselves and the points where they diverged.
+            let mut candidates = tips.clone();
+            for a in tips.iter() {
+                for b in tips.range(a..).skip(1) {
+                    if let Ok(base) = repo.merge_base(*a, *b) {
+                        candidates.insert(base);
+                    }
+                }
+            }
+
+            let mut eligible = Vec::new();
+            for candidate in candidates {
+                let votes = votes(repo, delegates, heads, candidate)?;
+
+                if votes >= quorum {
+                    eligible.push((candidate, votes));
+                }
+            }
+
+            // Out of the eligible commits, keep the ones that aren't in the history of
+            // another eligible commit. Ties are broken by votes, then commit time.
+            let mut best = Vec::new();
+            for (candidate, votes) in eligible.iter() {
+                let mut superseded = false;
+                for (other, _) in eligible.iter() {
+                    if other != candidate && repo.graph_descendant_of(*other, *candidate)? {
+                        superseded = true;
+                        break;
+                    }
+                }
+                if !superseded {
+                    let time = repo.find_commit(*candidate)?.time().seconds();
+                    best.push((*votes, time, *candidate));
+                }
+            }
+            best.into_iter()
+                .max()
+                .map(|(_, _, oid)| (oid, Rule::Quorum))
+        };
+
+        let (head, rule) = match head {
+            Some(head) => head,
+            None => return Ok(None),
+        };
+        let mut divergence = HashMap::new();
+        for (peer, oid) in heads {
+            let (ahead, behind) = repo.graph_ahead_behind(*oid, head)?;
+
+            divergence.insert(*peer, Divergence { ahead, behind });
+        }
+
+        Ok(Some(Self {
+            head,
+            rule,
+            quorum,
+            divergence,
+        }))
+    }
+}
+
+/// Number of delegates that have a commit in their history, ie. that have a peer whose head
+/// contains it.
+fn votes(
+    repo: &git2::Repository,
+    delegates: &[Delegate],
+    heads: &HashMap<PeerId, git2::Oid>,
+    commit: git2::Oid,
+) -> Result<usize, git2::Error> {
+    let mut votes = 0;
+    for delegate in delegates {
+        for peer in delegate.ids() {
+            if let Some(head) = heads.get(&peer) {
+                if contains(repo, *head, commit)? {
+                    votes += 1;
+                    break;
+                }
+            }
+        }
+    }
+    Ok(votes)
+}
+
+/// Whether `commit` is `head` or is in its history.
+fn contains(
+    repo: &git2::Repository,
+    head: git2::Oid,
+    commit: git2::Oid,
+) -> Result<bool, git2::Error> {
+    Ok(head == commit || repo.graph_descendant_of(head, commit)?)
+}
+
+/// Whether all tips are on a single line of history.
+fn is_linear(repo: &git2::Repository, tips: &BTreeSet<git2::Oid>) -> Result<bool, git2::Error> {
+    let mut tip = match tips.iter().next() {
+        Some(tip) => *tip,
+        None => return Ok(true),
+    };
+
+    for other in tips.iter().skip(1) {
+        if contains(repo, *other, tip)? {
+            tip = *other;
+        } else if !contains(repo, tip, *other)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
@@ -0,0 +1,176 @@
+//! Commit history filtering.
+use std::collections::HashSet;
+
+use regex::Regex;
+use shared::ssh;
+
+/// How commits are matched by author.
+#[derive(Debug)]
+pub enum Author {
+    /// Case-insensitive substring of the author's name or email.
+    Pattern(String),
+    /// Fingerprint of the SSH key of a peer, which must have signed the commit. Names and
+    /// emails are chosen freely, so they can't be trusted to identify a peer.
+    Key(String),
+}
+
+/// Criteria a commit must meet to be listed. Empty criteria match all commits.
+#[derive(Debug, Default)]
+pub struct Filter {
+    pub author: Option<Author>,
+    /// Pattern matched against the commit message.
+    pub grep: Option<Regex>,
+    /// Path prefixes, of which the commit must touch at least one.
+    pub paths: Vec<String>,
+    /// Minimum committer time, inclusive.
+    pub since: Option<i64>,
+    /// Maximum committer time, exclusive.
+    pub until: Option<i64>,
+}
+
+impl Filter {
+    /// Whether a commit matches.
+    pub fn matches(
+        &self,
+        repo: &git2::Repository,
+        commit: &git2::Commit,
+    ) -> Result<bool, git2::Error> {
+        let time = commit.time().seconds();
+
+        if self.since.is_some_and(|since| time < since) {
+            return Ok(false);
+        }
+        if self.until.is_some_and(|until| time >= until) {
+            return Ok(false);
+        }
+        if let Some(author) = &self.author {
+            let signature = commit.author();
+            let name = signature.name().unwrap_or_default();
+            let email = signature.email().unwrap_or_default();
+            let matches = match author {
+                Author::Pattern(pattern) => {
+                    let pattern = pattern.to_lowercase();
+
+                    name.to_lowercase().contains(&pattern)
+                        || email.to_lowercase().contains(&pattern)
+                }
+                Author::Key(fingerprint) => signed_by(repo, commit.id(), fingerprint),
+            };
+
+            if !matches {
+                return Ok(false);
+            }
+        }
+        if let Some(grep) = &self.grep {
+            if !grep.is_match(commit.message().unwrap_or_default()) {
+                return Ok(false);
+            }
+        }
+        if !self.paths.is_empty() {
+            // Compare against the first parent, like `git log -- <path>` does for linear history.
+            let parent = match commit.parent(0) {
+                Ok(parent) => Some(parent.tree()?),
+                Err(_) => None,
+            };
+            let mut opts = git2::DiffOptions::new();
+            for path in &self.paths {
+                opts.pathspec(path);
+            }
+            let diff =
+                repo.diff_tree_to_tree(parent.as_ref(), Some(&commit.tree()?), Some(&mut opts))?;
+
+            if diff.deltas().len() == 0 {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Whether a commit has a valid SSH signature by the key with the given fingerprint.
+fn signed_by(repo: &git2::Repository, oid: git2::Oid, fingerprint: &str) -> bool {
+    let (signature, data) = match repo.extract_signature(&oid, None) {
+        Ok(signed) => signed,
+        Err(_) => return false,
+    };
+    match ssh::Signature::from_armored(&String::from_utf8_lossy(&signature)) {
+        Some(signature) => {
+            signature.fingerprint() == fingerprint && signature.verify(&data) == ssh::Status::Valid
+        }
+        None => false,
+    }
+}
+
+/// Commits matching a filter.
+#[derive(Debug, Default)]
+pub struct Walk {
+    /// Matching commits, in the requested range.
+    pub commits: Vec<git2::Oid>,
+    /// Whether there are more matches after the range.
+    pub more: bool,
+    /// Stats about all matching commits, if requested.
+    pub stats: Option<Stats>,
+}
+
+/// Stats about the commits matching a filter.
+#[derive(Debug, Default)]
+pub struct Stats {
+    /// Number of matching commits.
+    pub commits: usize,
+    /// Emails of the authors of matching commits.
+    pub contributors: HashSet<String>,
+}
+
+/// Walk the history from `head`, newest first, and get the commits matching the filter.
+///
+/// The first `skip` matches are skipped, and at most `take` matches are returned, if given.
+/// The walk stops at the match after the last one returned, unless `stats` are requested,
+/// which requires walking the whole history.
+pub fn walk(
+    repo: &git2::Repository,
+    head: git2::Oid,
+    filter: &Filter,
+    skip: usize,
+    take: Option<usize>,
+    stats: bool,
+) -> Result<Walk, git2::Error> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+    revwalk.push(head)?;
+
+    let mut walk = Walk {
+        stats: if stats { Some(Stats::default()) } else { None },
+        ..Walk::default()
+    };
+    let mut matches = 0;
+
+    for oid in revwalk {
+        let oid = oid?;
+        let commit = repo.find_commit(oid)?;
+
+        if !filter.matches(repo, &commit)? {
+            continue;
+        }
+        matches += 1;
+
+        if let Some(stats) = &mut walk.stats {
+            stats.commits += 1;
+            stats
+                .contributors
+                .insert(String::from_utf8_lossy(commit.author().email_bytes()).into_owned());
+        }
+        if matches <= skip {
+            continue;
+        }
+        if take.is_some_and(|take| walk.commits.len() == take) {
+            walk.more = true;
+
+            if walk.stats.is_none() {
+                break;
+            }
+            continue;
+        }
+        walk.commits.push(oid);
+    }
+    Ok(walk)
+}
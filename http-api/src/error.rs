@@ -8,10 +8,6 @@ pub enum Error {
     #[error(transparent)]
     Git(#[from] git2::Error),
 
-    /// The namespace was expected in a reference but was not found.
-    #[error("missing namespace in reference")]
-    MissingNamespace,
-
     /// The project does not have a default branch.
     #[error("missing default branch in project")]
     MissingDefaultBranch,
@@ -28,10 +24,30 @@ pub enum Error {
     #[error("invalid branch name")]
     BranchName,
 
+    /// An invalid regular expression was given.
+    #[error(transparent)]
+    Pattern(#[from] regex::Error),
+
+    /// The requested highlighting theme does not exist.
+    #[error("unknown theme '{0}'")]
+    UnknownTheme(String),
+
+    /// Too many items were requested at once.
+    #[error("batch exceeds the maximum of {0} items")]
+    BatchSize(usize),
+
     /// The entity was not found.
     #[error("entity not found")]
     NotFound,
 
+    /// The request could not be authenticated.
+    #[error("unauthorized: {0}")]
+    Unauthorized(#[from] shared::auth::Error),
+
+    /// The authorization header is not supported.
+    #[error("unsupported authorization scheme")]
+    AuthorizationScheme,
+
     /// An error occured with radicle identities.
     #[error(transparent)]
     Identities(#[from] radicle_daemon::git::identities::Error),
@@ -48,6 +64,10 @@ pub enum Error {
     #[error(transparent)]
     Init(#[from] radicle_daemon::git::storage::read::error::Init),
 
+    /// An error occured with the project index.
+    #[error(transparent)]
+    ProjectIndex(#[from] project_index::Error),
+
     /// An error occured with radicle source.
     #[error(transparent)]
     Source(#[from] radicle_source::error::Error),
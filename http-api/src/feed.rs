@@ -0,0 +1,99 @@
+//! Atom feeds, see RFC 4287.
+use chrono::{SecondsFormat, TimeZone, Utc};
+
+/// Maximum number of entries in a feed.
+pub const FEED_LENGTH: usize = 50;
+
+/// An Atom feed.
+#[derive(Debug)]
+pub struct Feed {
+    /// Feed id, usually the URL of the feed.
+    pub id: String,
+    pub title: String,
+    /// Time of the last update, in seconds since epoch.
+    pub updated: i64,
+    pub entries: Vec<Entry>,
+}
+
+/// A feed entry.
+#[derive(Debug)]
+pub struct Entry {
+    pub id: String,
+    pub title: String,
+    /// Time of the entry, in seconds since epoch.
+    pub updated: i64,
+    pub author: String,
+    pub email: String,
+    pub summary: String,
+    /// Alternate representations of the entry, as `(content-type, url)` pairs.
+    pub links: Vec<(&'static str, String)>,
+}
+
+impl Feed {
+    /// Render the feed as XML.
+    pub fn render(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+
+        xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+        xml.push_str(&format!("  <id>{}</id>\n", escape(&self.id)));
+        xml.push_str(&format!("  <title>{}</title>\n", escape(&self.title)));
+        xml.push_str(&format!("  <updated>{}</updated>\n", time(self.updated)));
+        xml.push_str(&format!(
+            "  <link rel=\"self\" href=\"{}\"/>\n",
+            escape(&self.id)
+        ));
+
+        for entry in &self.entries {
+            xml.push_str("  <entry>\n");
+            xml.push_str(&format!("    <id>{}</id>\n", escape(&entry.id)));
+            xml.push_str(&format!("    <title>{}</title>\n", escape(&entry.title)));
+            xml.push_str(&format!("    <updated>{}</updated>\n", time(entry.updated)));
+            xml.push_str(&format!(
+                "    <author><name>{}</name><email>{}</email></author>\n",
+                escape(&entry.author),
+                escape(&entry.email)
+            ));
+            for (mime, href) in &entry.links {
+                xml.push_str(&format!(
+                    "    <link rel=\"alternate\" type=\"{}\" href=\"{}\"/>\n",
+                    mime,
+                    escape(href)
+                ));
+            }
+            xml.push_str(&format!(
+                "    <summary>{}</summary>\n",
+                escape(&entry.summary)
+            ));
+            xml.push_str("  </entry>\n");
+        }
+        xml.push_str("</feed>\n");
+        xml
+    }
+}
+
+/// Format a timestamp as an RFC 3339 date.
+fn time(seconds: i64) -> String {
+    Utc.timestamp_opt(seconds, 0)
+        .single()
+        .map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+/// Escape text for use in XML content and attributes.
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters are not allowed in XML 1.0.
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,70 @@
+//! Forks: tracked peers' branches, and how they diverge from the canonical head.
+use radicle_daemon::PeerId;
+use serde::Serialize;
+
+use crate::project::string;
+
+/// A branch of a fork.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Branch {
+    /// Branch name, eg. `master`.
+    pub name: String,
+    /// Branch head.
+    #[serde(with = "string")]
+    pub head: git2::Oid,
+    /// Commits on the branch that are not in the canonical head.
+    pub ahead: Option<usize>,
+    /// Commits in the canonical head that are not on the branch.
+    pub behind: Option<usize>,
+    /// Committer time of the branch head, in seconds since epoch.
+    pub timestamp: i64,
+}
+
+impl Branch {
+    /// Load a branch, comparing it against `base`, if any.
+    pub fn new(
+        repo: &git2::Repository,
+        name: String,
+        head: git2::Oid,
+        base: Option<git2::Oid>,
+    ) -> Result<Self, git2::Error> {
+        let timestamp = repo.find_commit(head)?.time().seconds();
+        let (ahead, behind) = match base {
+            Some(base) => {
+                let (ahead, behind) = repo.graph_ahead_behind(head, base)?;
+                (Some(ahead), Some(behind))
+            }
+            None => (None, None),
+        };
+
+        Ok(Self {
+            name,
+            head,
+            ahead,
+            behind,
+            timestamp,
+        })
+    }
+}
+
+/// A tracked peer's view of a project.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fork {
+    /// Peer id.
+    pub id: PeerId,
+    /// Name of the peer's personal identity, if known.
+    pub name: Option<String>,
+    /// Whether the peer is a project delegate.
+    pub delegate: bool,
+    /// The peer's branches.
+    pub branches: Vec<Branch>,
+}
+
+impl Fork {
+    /// Time of the most recent branch update, if any.
+    pub fn timestamp(&self) -> Option<i64> {
+        self.branches.iter().map(|b| b.timestamp).max()
+    }
+}
@@ -0,0 +1,151 @@
+//! Commit graph across branches, for rendering history as lanes.
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::project::string;
+
+/// A commit in the graph.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Node {
+    #[serde(with = "string")]
+    pub id: git2::Oid,
+    /// Parent commits. Parents outside of the graph bounds are included.
+    pub parents: Vec<String>,
+    /// Lane the commit is drawn in, starting at zero.
+    pub lane: usize,
+    /// Labels of the refs pointing to this commit.
+    pub refs: Vec<String>,
+    pub summary: String,
+    pub author: String,
+    /// Committer time, in seconds since epoch.
+    pub timestamp: i64,
+}
+
+/// A commit graph.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Graph {
+    /// Commits, in topological order, newest first.
+    pub nodes: Vec<Node>,
+    /// Number of lanes used.
+    pub lanes: usize,
+    /// Whether the graph was cut short by the commit limit.
+    pub truncated: bool,
+}
+
+/// Bounds of a graph.
+#[derive(Debug, Clone, Copy)]
+pub struct Bounds {
+    /// Maximum number of commits.
+    pub limit: usize,
+    /// Minimum committer time, inclusive.
+    pub since: Option<i64>,
+    /// Maximum committer time, exclusive.
+    pub until: Option<i64>,
+}
+
+impl Graph {
+    /// Build the graph of the history reachable from the given refs, as `(label, oid)` pairs.
+    ///
+    /// Lanes are assigned by walking the history newest first: a commit takes the lane that
+    /// was reserved for it by a child, and passes it on to its first parent. Other parents are
+    /// given new lanes unless they already have one, and lanes are freed once the commit they
+    /// were reserved for is reached.
+    pub fn build(
+        repo: &git2::Repository,
+        refs: &[(String, git2::Oid)],
+        bounds: Bounds,
+    ) -> Result<Self, git2::Error> {
+        let mut labels: HashMap<git2::Oid, Vec<String>> = HashMap::new();
+        let mut walk = repo.revwalk()?;
+        walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+
+        for (label, oid) in refs {
+            labels.entry(*oid).or_default().push(label.clone());
+            walk.push(*oid)?;
+        }
+
+        // Each lane holds the commit it is reserved for, if any.
+        let mut lanes: Vec<Option<git2::Oid>> = Vec::new();
+        let mut nodes = Vec::new();
+        let mut width = 0;
+        let mut truncated = false;
+
+        for oid in walk {
+            let oid = oid?;
+            let commit = repo.find_commit(oid)?;
+            let time = commit.time().seconds();
+            let reserved = lanes
+                .iter()
+                .enumerate()
+                .filter(|(_, l)| **l == Some(oid))
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+
+            // Free the lanes reserved for this commit. If it's out of bounds, its
+            // ancestry won't be drawn.
+            for i in reserved.iter() {
+                lanes[*i] = None;
+            }
+            // Commit times can be skewed, so newer commits may still follow an old one.
+            if bounds.since.is_some_and(|since| time < since) {
+                continue;
+            }
+            if bounds.until.is_some_and(|until| time >= until) {
+                continue;
+            }
+            if nodes.len() == bounds.limit {
+                truncated = true;
+                break;
+            }
+
+            let lane = match reserved.first() {
+                Some(lane) => *lane,
+                None => free_lane(&mut lanes),
+            };
+            let parents = commit.parent_ids().collect::<Vec<_>>();
+
+            // The lane is reserved for the first parent even if another lane already is, so
+            // that it isn't reused before the lanes meet at the parent.
+            if let Some(first) = parents.first() {
+                lanes[lane] = Some(*first);
+            }
+            for parent in parents.iter().skip(1) {
+                if !lanes.contains(&Some(*parent)) {
+                    let i = free_lane(&mut lanes);
+                    lanes[i] = Some(*parent);
+                }
+            }
+            width = width.max(lanes.len()).max(lane + 1);
+
+            nodes.push(Node {
+                id: oid,
+                parents: parents.iter().map(|p| p.to_string()).collect(),
+                lane,
+                refs: labels.remove(&oid).unwrap_or_default(),
+                summary: commit.summary().unwrap_or_default().to_owned(),
+                author: commit.author().name().unwrap_or_default().to_owned(),
+                timestamp: time,
+            });
+        }
+
+        Ok(Self {
+            nodes,
+            lanes: width,
+            truncated,
+        })
+    }
+}
+
+/// Get the first free lane, adding one if necessary.
+fn free_lane(lanes: &mut Vec<Option<git2::Oid>>) -> usize {
+    match lanes.iter().position(|l| l.is_none()) {
+        Some(i) => i,
+        None => {
+            lanes.push(None);
+            lanes.len() - 1
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Syntax highlighting with CSS classes, and theme stylesheets.
+use std::path::Path;
+
+use serde::Deserialize;
+use syntect::highlighting::ThemeSet;
+use syntect::html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+/// Class style used in classed mode. Classes are prefixed so as not to clash with the
+/// client's own classes, eg. `hl-keyword`.
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+
+/// Highlighting output mode.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    /// Colors are inlined as styles, using a single theme.
+    #[default]
+    Inline,
+    /// CSS class names are emitted, to be styled with a theme stylesheet.
+    Classed,
+}
+
+/// Syntax definitions and themes, loaded once.
+pub struct Highlighter {
+    syntaxes: SyntaxSet,
+    themes: ThemeSet,
+}
+
+impl std::fmt::Debug for Highlighter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Highlighter").finish()
+    }
+}
+
+impl Default for Highlighter {
+    fn default() -> Self {
+        Self {
+            syntaxes: SyntaxSet::load_defaults_newlines(),
+            themes: ThemeSet::load_defaults(),
+        }
+    }
+}
+
+impl Highlighter {
+    /// Names of the available themes, sorted.
+    pub fn themes(&self) -> Vec<&str> {
+        // Theme sets are stored in a `BTreeMap`.
+        self.themes.themes.keys().map(|k| k.as_str()).collect()
+    }
+
+    /// Whether a theme exists.
+    pub fn has_theme(&self, name: &str) -> bool {
+        self.themes.themes.contains_key(name)
+    }
+
+    /// Stylesheet for classed output, using the given theme.
+    pub fn css(&self, name: &str) -> Option<String> {
+        let theme = self.themes.themes.get(name)?;
+
+        Some(css_for_theme_with_class_style(theme, CLASS_STYLE))
+    }
+
+    /// Highlight a file, emitting CSS classes. The syntax is detected from the file
+    /// extension, then from the first line.
+    pub fn classed(&self, path: &str, content: &str) -> String {
+        let syntax = Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| self.syntaxes.find_syntax_by_extension(ext))
+            .or_else(|| self.syntaxes.find_syntax_by_first_line(content))
+            .unwrap_or_else(|| self.syntaxes.find_syntax_plain_text());
+        let mut generator =
+            ClassedHTMLGenerator::new_with_class_style(syntax, &self.syntaxes, CLASS_STYLE);
+
+        for line in LinesWithEndings::from(content) {
+            generator.parse_html_for_line_which_includes_newline(line);
+        }
+        format!("<pre class=\"hl-code\">{}</pre>", generator.finalize())
+    }
+}
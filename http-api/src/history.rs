@@ -0,0 +1,142 @@
+//! File and directory history, following renames.
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::patches::Commit;
+
+/// How a commit changed a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Added,
+    Modified,
+    Renamed,
+    Deleted,
+}
+
+/// A commit that touched a file or directory.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry {
+    pub commit: Commit,
+    /// Path in this commit.
+    pub path: String,
+    /// Path before this commit, if it was renamed.
+    pub old_path: Option<String>,
+    pub status: Status,
+}
+
+/// Get the commits that touched a path, newest first, starting from `head`. The walk stops
+/// once `limit` commits are found, if given.
+///
+/// The path may be a file or a directory, which is touched whenever anything under it
+/// changes. When a commit renames a file, older commits are looked up under the old path.
+/// Merge commits are only included if the path differs from all of their parents, ie. if the
+/// merge itself changed it.
+pub fn file_history(
+    repo: &git2::Repository,
+    head: git2::Oid,
+    path: &str,
+    limit: Option<usize>,
+) -> Result<Vec<Entry>, git2::Error> {
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+    walk.push(head)?;
+
+    // Path of the file in each commit yet to be visited. Since the walk mixes branches, the
+    // path is passed on from children to parents, so that renames only apply to the history
+    // of the commit that made them.
+    let mut paths = HashMap::new();
+    let mut entries = Vec::new();
+
+    paths.insert(head, path.to_owned());
+
+    for oid in walk {
+        if limit.is_some_and(|limit| entries.len() == limit) {
+            break;
+        }
+        let oid = oid?;
+        let path = match paths.remove(&oid) {
+            Some(path) => path,
+            None => continue,
+        };
+        let commit = repo.find_commit(oid)?;
+        let tree = commit.tree()?;
+        let current = entry_at(&tree, &path);
+        let parents = commit
+            .parents()
+            .map(|p| p.tree())
+            .collect::<Result<Vec<_>, _>>()?;
+        let previous = parents
+            .iter()
+            .map(|t| entry_at(t, &path))
+            .collect::<Vec<_>>();
+
+        // Skip commits that didn't touch the path, or merges that took it as is from a parent.
+        let untouched = previous.contains(&current)
+            || (current.is_none() && previous.iter().all(|p| p.is_none()));
+        let (status, old_path) = if untouched {
+            (None, None)
+        } else {
+            match current {
+                None => (Some(Status::Deleted), None),
+                Some(_) if previous.iter().any(|p| p.is_some()) => (Some(Status::Modified), None),
+                Some(_) => match parents.first() {
+                    Some(parent) => match renamed_from(repo, parent, &tree, &path)? {
+                        Some(old_path) => (Some(Status::Renamed), Some(old_path)),
+                        None => (Some(Status::Added), None),
+                    },
+                    None => (Some(Status::Added), None),
+                },
+            }
+        };
+
+        // A rename is relative to the first parent, other parents keep the current path.
+        for (i, parent) in commit.parent_ids().enumerate() {
+            let path = match &old_path {
+                Some(old_path) if i == 0 => old_path.clone(),
+                _ => path.clone(),
+            };
+            paths.entry(parent).or_insert(path);
+        }
+        if let Some(status) = status {
+            entries.push(Entry {
+                commit: Commit::from(&commit),
+                path,
+                old_path,
+                status,
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Get the blob or tree at a path in a tree. Since objects are content-addressed, a path
+/// changed between two trees iff its oid did.
+fn entry_at(tree: &git2::Tree, path: &str) -> Option<git2::Oid> {
+    tree.get_path(Path::new(path)).ok().map(|e| e.id())
+}
+
+/// If `path` was renamed between two trees, get its old path.
+fn renamed_from(
+    repo: &git2::Repository,
+    old: &git2::Tree,
+    new: &git2::Tree,
+    path: &str,
+) -> Result<Option<String>, git2::Error> {
+    let mut diff = repo.diff_tree_to_tree(Some(old), Some(new), None)?;
+    diff.find_similar(Some(git2::DiffFindOptions::new().renames(true)))?;
+
+    let old_path = diff
+        .deltas()
+        .find(|d| {
+            d.status() == git2::Delta::Renamed && d.new_file().path() == Some(Path::new(path))
+        })
+        .and_then(|d| d.old_file().path())
+        .and_then(|p| p.to_str())
+        .map(|p| p.to_owned());
+
+    Ok(old_path)
+}
@@ -0,0 +1,261 @@
+//! Issues, stored as commits under `refs/remotes/<peer>/cobs/issues/<id>`.
+//!
+//! An issue is a graph of change commits. Its id is the oid of the root commit, which creates
+//! the issue. Every commit holds a single change in a `change.json` blob at the root of its
+//! tree, and has as parents the latest changes known to its author, which may come from other
+//! peers. Changes are signed with the SSH key of their author. Each peer publishes its view of
+//! the issue under its own remote refs, so that issues replicate like any other ref. The state
+//! of an issue is obtained by applying the changes published by all tracked peers in causal
+//! order.
+//!
+//! Changes are JSON objects tagged by `type`:
+//!
+//! * `{ "type": "create", "title": "…", "body": "…" }`, only valid as the root commit.
+//! * `{ "type": "edit", "title": "…", "body": "…" }`, both fields optional.
+//! * `{ "type": "comment", "body": "…" }`
+//! * `{ "type": "state", "state": "open" | "closed" }`
+use std::collections::{BTreeMap, HashSet};
+
+use radicle_daemon::{PeerId, Urn};
+use serde::{Deserialize, Serialize};
+use shared::issues::{CHANGE_BLOB, ISSUES_PREFIX};
+
+use crate::project::string;
+use crate::signature::{Signature, Signer};
+
+/// A change to an issue.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Change {
+    Create {
+        title: String,
+        #[serde(default)]
+        body: String,
+    },
+    Edit {
+        title: Option<String>,
+        body: Option<String>,
+    },
+    Comment {
+        body: String,
+    },
+    State {
+        state: State,
+    },
+}
+
+/// Issue state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum State {
+    Open,
+    Closed,
+}
+
+/// Author of a change, as recorded in the commit.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Author {
+    pub name: String,
+    pub email: String,
+}
+
+impl<'a> From<git2::Signature<'a>> for Author {
+    fn from(other: git2::Signature<'a>) -> Self {
+        Self {
+            name: other.name().unwrap_or_default().to_owned(),
+            email: other.email().unwrap_or_default().to_owned(),
+        }
+    }
+}
+
+/// A comment on an issue.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Comment {
+    /// Oid of the change that added the comment.
+    #[serde(with = "string")]
+    pub id: git2::Oid,
+    pub author: Author,
+    pub body: String,
+    pub timestamp: i64,
+}
+
+/// A change of state of an issue, eg. closing it.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateChange {
+    /// Oid of the change.
+    #[serde(with = "string")]
+    pub id: git2::Oid,
+    pub author: Author,
+    pub state: State,
+    pub timestamp: i64,
+}
+
+/// An issue, with all known changes applied.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Issue {
+    #[serde(with = "string")]
+    pub id: git2::Oid,
+    pub title: String,
+    pub body: String,
+    pub state: State,
+    pub author: Author,
+    /// Creation time.
+    pub timestamp: i64,
+    /// Time of the latest change.
+    pub updated: i64,
+    pub comments: Vec<Comment>,
+    pub state_changes: Vec<StateChange>,
+    /// Peers publishing this issue.
+    pub peers: Vec<PeerId>,
+}
+
+impl Issue {
+    /// Load an issue from the tips published by peers.
+    ///
+    /// Returns `None` if the issue root is missing or isn't a valid creation.
+    /// Changes that can't be parsed, that aren't signed by one of the peers, or that aren't
+    /// descendants of the root, are ignored.
+    pub fn load(
+        repo: &git2::Repository,
+        id: git2::Oid,
+        tips: &[(PeerId, git2::Oid)],
+    ) -> Result<Option<Self>, git2::Error> {
+        let mut walk = repo.revwalk()?;
+        walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME | git2::Sort::REVERSE)?;
+
+        for (_, tip) in tips {
+            walk.push(*tip)?;
+        }
+
+        // Changes are published together with the ones they build on, so a change may come
+        // from any of the peers.
+        let signers = tips
+            .iter()
+            .map(|(peer, _)| Signer {
+                peer: *peer,
+                name: None,
+                delegate: false,
+            })
+            .collect::<Vec<_>>();
+
+        let mut issue: Option<Self> = None;
+        for oid in walk {
+            let oid = oid?;
+            let signed = Signature::load(repo, oid, &signers)?.is_some_and(|s| s.signer.is_some());
+            if !signed {
+                tracing::warn!("Ignoring unsigned change {} in issue {}", oid, id);
+                continue;
+            }
+            let commit = repo.find_commit(oid)?;
+            let change = match load_change(repo, &commit) {
+                Some(change) => change,
+                None => {
+                    tracing::warn!("Ignoring invalid change {} in issue {}", oid, id);
+                    continue;
+                }
+            };
+            let author = Author::from(commit.author());
+            let timestamp = commit.time().seconds();
+
+            let issue = match (issue.as_mut(), change) {
+                (None, Change::Create { title, body }) if oid == id => {
+                    issue = Some(Self {
+                        id,
+                        title,
+                        body,
+                        state: State::Open,
+                        author,
+                        timestamp,
+                        updated: timestamp,
+                        comments: Vec::new(),
+                        state_changes: Vec::new(),
+                        peers: tips.iter().map(|(peer, _)| *peer).collect(),
+                    });
+                    continue;
+                }
+                // The root comes first, as the walk is in causal order: changes before it
+                // don't belong to the issue.
+                (None, _) => continue,
+                (Some(_), _) if !repo.graph_descendant_of(oid, id)? => continue,
+                (Some(issue), change) => (issue, change),
+            };
+            let (issue, change) = issue;
+
+            match change {
+                Change::Create { .. } => continue,
+                Change::Edit { title, body } => {
+                    if let Some(title) = title {
+                        issue.title = title;
+                    }
+                    if let Some(body) = body {
+                        issue.body = body;
+                    }
+                }
+                Change::Comment { body } => issue.comments.push(Comment {
+                    id: oid,
+                    author,
+                    body,
+                    timestamp,
+                }),
+                Change::State { state } => {
+                    issue.state = state;
+                    issue.state_changes.push(StateChange {
+                        id: oid,
+                        author,
+                        state,
+                        timestamp,
+                    });
+                }
+            }
+            issue.updated = issue.updated.max(timestamp);
+        }
+        Ok(issue)
+    }
+}
+
+fn load_change(repo: &git2::Repository, commit: &git2::Commit) -> Option<Change> {
+    let entry = commit.tree().ok()?.get_name(CHANGE_BLOB)?.to_owned();
+    let blob = repo.find_blob(entry.id()).ok()?;
+
+    serde_json::from_slice(blob.content()).ok()
+}
+
+/// Find the issue tips published by the given peers in a project, by issue id.
+pub fn tips(
+    repo: &git2::Repository,
+    urn: &Urn,
+    peers: &HashSet<PeerId>,
+) -> Result<BTreeMap<git2::Oid, Vec<(PeerId, git2::Oid)>>, git2::Error> {
+    let prefix = format!("refs/namespaces/{}/refs/remotes/", urn.encode_id());
+    let mut issues: BTreeMap<_, Vec<_>> = BTreeMap::new();
+
+    for reference in repo.references_glob(&format!("{}*/{}*", prefix, ISSUES_PREFIX))? {
+        let reference = reference?;
+        let name = match reference.name().and_then(|n| n.strip_prefix(&prefix)) {
+            Some(name) => name,
+            None => continue,
+        };
+        let (peer, id) = match name.split_once('/') {
+            Some((peer, rest)) => match rest.strip_prefix(ISSUES_PREFIX) {
+                Some(id) => (peer, id),
+                None => continue,
+            },
+            None => continue,
+        };
+        let (peer, id) = match (PeerId::from_default_encoding(peer), git2::Oid::from_str(id)) {
+            (Ok(peer), Ok(id)) => (peer, id),
+            _ => continue,
+        };
+        if !peers.contains(&peer) {
+            continue;
+        }
+        if let Ok(commit) = reference.peel_to_commit() {
+            issues.entry(id).or_default().push((peer, commit.id()));
+        }
+    }
+    Ok(issues)
+}
@@ -1,19 +1,37 @@
 #![allow(clippy::if_same_then_else)]
+mod activity;
+mod badge;
+mod canonical;
+mod commits;
 mod error;
+mod feed;
+mod forks;
+mod graph;
+mod highlight;
+mod history;
+mod issues;
+mod notes;
+mod patches;
+mod payload;
 mod project;
+mod readme;
+mod refs;
+mod revision;
+mod signature;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryFrom as _;
 use std::convert::TryInto as _;
 use std::net;
 use std::path::PathBuf;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
+use chrono::TimeZone as _;
+use either::Either;
 use radicle_source::commit::Header;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tokio::sync::RwLock;
 use warp::hyper::StatusCode;
 use warp::reply::Json;
 use warp::{self, filters::BoxedFilter, path, query, Filter, Rejection, Reply};
@@ -21,13 +39,16 @@ use warp::{self, filters::BoxedFilter, path, query, Filter, Rejection, Reply};
 use radicle_daemon::librad::git::identities;
 use radicle_daemon::librad::git::storage::read::ReadOnly;
 use radicle_daemon::librad::git::tracking;
-use radicle_daemon::librad::git::types::{One, Reference, Single};
+use radicle_daemon::librad::git::types::Reference;
 use radicle_daemon::{git::types::Namespace, Paths, PeerId, Urn};
-use radicle_source::surf::file_system::Path;
 use radicle_source::surf::vcs::git;
 use radicle_source::surf::vcs::git::RepositoryRef;
 
+use project_index::Delegation;
+
+use crate::canonical::Canonical;
 use crate::project::Info;
+use crate::revision::Revision;
 
 use error::Error;
 
@@ -40,55 +61,280 @@ pub struct Options {
     pub tls_cert: Option<PathBuf>,
     pub tls_key: Option<PathBuf>,
     pub theme: String,
+    pub blob_limit: usize,
+    pub preview_limit: usize,
+    pub quorum: Option<usize>,
+    pub web_url: Option<String>,
+    /// Notes refs to read commit notes from, eg. `refs/notes/ci`.
+    pub notes_refs: Vec<String>,
+    /// Operator alias file, mapping aliases to project URNs.
+    pub alias_file: Option<PathBuf>,
+    /// Policy file deciding which projects are served.
+    pub policy_file: Option<PathBuf>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 struct CommitsQueryString {
     parent: Option<String>,
+    peer: Option<PeerId>,
+    since: Option<i64>,
+    until: Option<i64>,
+    /// Author name or email, or a peer id, matching the commits signed by the peer's key.
+    author: Option<String>,
+    /// Regular expression matched against commit messages, case-insensitive.
+    grep: Option<String>,
+    /// Comma-separated path prefixes.
+    path: Option<String>,
+    /// Whether to include the signature and notes of each commit. Defaults to `true` for
+    /// paginated requests only, since verifying signatures of long histories is slow.
+    details: Option<bool>,
+    /// Whether to include stats about all matching commits. Defaults to `true` for requests
+    /// that aren't paginated only, since the whole history has to be walked.
+    stats: Option<bool>,
+}
+
+/// Maximum number of projects in a batch request.
+const MAX_BATCH_SIZE: usize = 100;
+/// Maximum size of a batch request body, in bytes.
+const MAX_BATCH_BODY: u64 = 64 * 1024;
+
+/// Body of a batch project request.
+#[derive(Deserialize)]
+struct BatchRequest {
+    /// Project URNs or aliases.
+    projects: Vec<String>,
+}
+
+/// Query string accepted by feed endpoints.
+#[derive(Serialize, Deserialize, Clone)]
+struct FeedQueryString {
+    #[serde(rename = "ref")]
+    revision: Option<String>,
+    peer: Option<PeerId>,
+}
+
+/// Order of project listings.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ProjectSort {
+    /// By name, ascending.
+    #[default]
+    Name,
+    /// By most recent activity, newest first.
+    Updated,
+    /// By creation time, newest first.
+    Created,
+}
+
+/// Query string accepted by the project listing.
+#[derive(Serialize, Deserialize, Clone)]
+struct ProjectsQueryString {
+    sort: Option<ProjectSort>,
+    /// Case-insensitive substring of the project name or description.
+    q: Option<String>,
+    /// Peer id or personal identity URN of a delegate.
+    delegate: Option<String>,
+}
+
+/// Query string accepted by paginated endpoints. Pages start at zero.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct PageQueryString {
+    page: Option<usize>,
+    per_page: Option<usize>,
+}
+
+impl PageQueryString {
+    /// Default number of items per page.
+    const PER_PAGE: usize = 30;
+    /// Maximum number of items per page.
+    const MAX_PER_PAGE: usize = 100;
+
+    /// Requested page.
+    fn page(&self) -> usize {
+        self.page.unwrap_or_default()
+    }
+
+    /// Requested number of items per page.
+    fn per_page(&self) -> usize {
+        self.per_page
+            .unwrap_or(Self::PER_PAGE)
+            .clamp(1, Self::MAX_PER_PAGE)
+    }
+
+    /// Whether pagination was requested.
+    fn is_requested(&self) -> bool {
+        self.page.is_some() || self.per_page.is_some()
+    }
+
+    /// Get the requested page of items, along with pagination details.
+    fn paginate<T: Serialize>(&self, items: Vec<T>) -> serde_json::Value {
+        let page = self.page();
+        let per_page = self.per_page();
+        let total = items.len();
+        let items = items
+            .into_iter()
+            .skip(page.saturating_mul(per_page))
+            .take(per_page)
+            .collect::<Vec<_>>();
+
+        json!({
+            "items": items,
+            "page": page,
+            "perPage": per_page,
+            "total": total,
+        })
+    }
+}
+
+/// Query string accepted by the badge endpoint.
+#[derive(Deserialize, Clone)]
+struct BadgeQueryString {
+    label: Option<String>,
+    value: Option<badge::Value>,
+}
+
+/// Query string accepted by the blob endpoint, to control syntax highlighting.
+#[derive(Deserialize, Clone)]
+struct HighlightQueryString {
+    highlight: bool,
+    /// Theme used in inline mode. Defaults to the theme given at startup.
+    theme: Option<String>,
+    mode: Option<highlight::Mode>,
+}
+
+/// Query string accepted by the graph endpoint.
+#[derive(Deserialize, Clone)]
+struct GraphQueryString {
+    /// Comma-separated revisions to start from.
+    refs: Option<String>,
+    limit: Option<usize>,
     since: Option<i64>,
     until: Option<i64>,
 }
 
+impl GraphQueryString {
+    /// Default number of commits.
+    const LIMIT: usize = 200;
+    /// Maximum number of commits.
+    const MAX_LIMIT: usize = 2000;
+}
+
+/// Query string accepted by source endpoints, to resolve revisions in a peer's refs.
+#[derive(Serialize, Deserialize, Clone)]
+struct PeerQueryString {
+    peer: Option<PeerId>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Context {
     paths: Paths,
     theme: String,
-    aliases: Arc<RwLock<HashMap<String, Urn>>>,
+    highlighter: Arc<highlight::Highlighter>,
+    blob_limit: usize,
+    preview_limit: usize,
+    quorum: Option<usize>,
+    tls: bool,
+    web_url: Option<String>,
+    notes_refs: Vec<String>,
+    /// Info of indexed projects, along with the digest of the project in the index it was
+    /// computed from, so that it is only computed again when the project changes.
+    infos: Arc<Mutex<HashMap<Urn, (String, Arc<Info>)>>>,
+    /// Peer id of the seed, which authentication tokens are bound to.
+    peer_id: PeerId,
+    /// Index of projects and persons, shared with the other services.
+    projects: project_index::Index,
 }
 
 impl Context {
-    /// Populates alias map with unique projects' names and their urns
-    async fn populate_aliases(&self, map: &mut HashMap<String, Urn>) -> Result<(), Error> {
-        use radicle_daemon::git::identities::SomeIdentity::Project;
+    /// Whether the seed's policy allows serving a project to the given reader.
+    ///
+    /// The policy may depend on the project's delegates, so projects that aren't indexed yet
+    /// are treated as not found. The index is kept up to date by watching the monorepo, rather
+    /// than refreshed here, so that requests for unknown URNs can't trigger refreshes.
+    fn can_read(&self, urn: &Urn, reader: Option<&PeerId>) -> bool {
+        self.projects.contains(urn) && self.projects.can_read(urn, reader)
+    }
 
-        let storage = ReadOnly::open(&self.paths).expect("failed to open storage");
-        let identities = identities::any::list(&storage)?;
+    /// Get the info of an indexed project, cached until the project changes.
+    fn indexed_info(
+        &self,
+        repo: &git2::Repository,
+        storage: &ReadOnly,
+        urn: &Urn,
+    ) -> Result<Arc<Info>, Error> {
+        let digest = self.projects.digest(urn).ok_or(Error::NotFound)?;
 
-        for identity in identities.flatten() {
-            if let Project(project) = identity {
-                let urn = project.urn();
-                let name = (&project.payload().subject.name).to_string();
+        self.cached_info(repo, storage, urn, digest)
+    }
 
-                if let std::collections::hash_map::Entry::Vacant(e) = map.entry(name.clone()) {
-                    e.insert(urn);
-                }
+    /// Get the info of a project, cached as long as the index digest stays the same.
+    fn cached_info(
+        &self,
+        repo: &git2::Repository,
+        storage: &ReadOnly,
+        urn: &Urn,
+        digest: String,
+    ) -> Result<Arc<Info>, Error> {
+        if let Some((cached, info)) = self
+            .infos
+            .lock()
+            .expect("info cache lock is not poisoned")
+            .get(urn)
+        {
+            if *cached == digest {
+                return Ok(info.clone());
             }
         }
+        let project = identities::project::get(storage, urn)?.ok_or(Error::NotFound)?;
+        let meta: project::Metadata = project.try_into()?;
+        let info = Arc::new(get_info(repo, meta, self.quorum));
 
-        Ok(())
+        self.infos
+            .lock()
+            .expect("info cache lock is not poisoned")
+            .insert(urn.clone(), (digest, info.clone()));
+
+        Ok(info)
     }
 }
 
-/// Run the HTTP API.
-pub async fn run(options: Options) {
+/// Run the HTTP API. Fails if the storage or project index can't be initialized, eg. because
+/// of an invalid alias or policy file.
+pub async fn run(options: Options) -> Result<(), Error> {
     let paths = Paths::from_root(options.root).unwrap();
-    let storage = ReadOnly::open(&paths).expect("failed to read storage paths");
+    let storage = ReadOnly::open(&paths)?;
     let peer_id = storage.peer_id().to_owned();
 
+    let projects = project_index::Index::new(
+        paths.clone(),
+        project_index::Config {
+            alias_file: options.alias_file,
+            policy_file: options.policy_file,
+        },
+    );
+    projects.refresh()?;
+    if let Err(e) = projects.watch() {
+        tracing::warn!("Project index won't be refreshed on changes: {}", e);
+    }
+
     let ctx = Context {
         paths,
-        aliases: Default::default(),
+        projects,
         theme: options.theme,
+        highlighter: Default::default(),
+        blob_limit: options.blob_limit,
+        preview_limit: options.preview_limit,
+        quorum: options.quorum,
+        tls: options.tls_cert.is_some() && options.tls_key.is_some(),
+        web_url: options.web_url,
+        notes_refs: if options.notes_refs.is_empty() {
+            vec![notes::DEFAULT_NOTES_REF.to_owned()]
+        } else {
+            options.notes_refs
+        },
+        infos: Default::default(),
+        peer_id,
     };
 
     let v1 = warp::path("v1");
@@ -99,20 +345,106 @@ pub async fn run(options: Options) {
 
     let projects = path("projects").and(filters(ctx.clone()));
 
-    let delegates = path("delegates").and(
+    let auth = {
+        let ctx = ctx.clone();
+        path("auth")
+            .and(path("challenge"))
+            .and(warp::get().and(path::end()))
+            .map(move || ctx.clone())
+            .and_then(challenge_handler)
+    };
+
+    let activity = {
+        let ctx = ctx.clone();
+        let reader = reader_filter(ctx.clone());
+        path("activity.atom")
+            .and(warp::get().and(path::end()))
+            .map(move || ctx.clone())
+            .and(reader)
+            .and(warp::header::optional::<String>("host"))
+            .and_then(activity_feed_handler)
+    };
+
+    let activity_log = {
+        let ctx = ctx.clone();
+        let reader = reader_filter(ctx.clone());
+        path("activity")
+            .and(warp::get().and(path::end()))
+            .map(move || ctx.clone())
+            .and(reader)
+            .and(query::<PageQueryString>())
+            .and_then(activity_handler)
+    };
+
+    let themes = {
+        let ctx = ctx.clone();
+        path("highlight")
+            .and(path("themes"))
+            .and(warp::get().and(path::end()))
+            .map(move || ctx.clone())
+            .and_then(themes_handler)
+    };
+
+    let theme_css = {
+        let ctx = ctx.clone();
         warp::get()
             .map(move || ctx.clone())
-            .and(path::param::<Urn>())
-            .and(path("projects"))
+            .and(path("highlight"))
+            .and(path("themes"))
+            .and(path::param::<String>())
             .and(path::end())
-            .and_then(delegates_projects_handler),
-    );
+            .and_then(theme_css_handler)
+    };
+
+    let delegates = {
+        let ctx = ctx.clone();
+        let reader = reader_filter(ctx.clone());
+        path("delegates").and(
+            warp::get()
+                .map(move || ctx.clone())
+                .and(reader)
+                .and(path::param::<Urn>())
+                .and(path("projects"))
+                .and(path::end())
+                .and_then(delegates_projects_handler),
+        )
+    };
+
+    let aliases = {
+        let ctx = ctx.clone();
+        let reader = reader_filter(ctx.clone());
+        path("aliases")
+            .and(warp::get().and(path::end()))
+            .map(move || ctx.clone())
+            .and(reader)
+            .and_then(aliases_handler)
+    };
+
+    let peer_projects = {
+        let reader = reader_filter(ctx.clone());
+        path("peers").and(
+            warp::get()
+                .map(move || ctx.clone())
+                .and(reader)
+                .and(path::param::<PeerId>())
+                .and(path("projects"))
+                .and(path::end())
+                .and_then(peer_projects_handler),
+        )
+    };
 
     let routes = path::end()
         .and_then(root_handler)
         .or(v1.and(peer))
         .or(v1.and(projects))
+        .or(v1.and(activity))
+        .or(v1.and(activity_log))
+        .or(v1.and(themes))
+        .or(v1.and(theme_css))
         .or(v1.and(delegates))
+        .or(v1.and(peer_projects))
+        .or(v1.and(aliases))
+        .or(v1.and(auth))
         .recover(recover)
         .with(warp::cors().allow_any_origin())
         .with(warp::log("http::api"));
@@ -129,6 +461,19 @@ pub async fn run(options: Options) {
     } else {
         server.run(options.listen).await
     }
+    Ok(())
+}
+
+/// Issue a challenge for peers to sign with their key, to authenticate as readers of private
+/// projects. The signed challenge is passed as an `Authorization: Bearer <token>` header.
+/// `GET /v1/auth/challenge`
+async fn challenge_handler(ctx: Context) -> Result<impl Reply, Rejection> {
+    let expiry = shared::auth::expiry();
+    let response = json!({
+        "challenge": shared::auth::challenge(&ctx.peer_id, expiry),
+        "expiry": expiry,
+    });
+    Ok(warp::reply::json(&response))
 }
 
 /// Return the peer id for the node identity.
@@ -140,11 +485,42 @@ async fn peer_handler(peer_id: PeerId) -> Result<impl warp::Reply, warp::Rejecti
     Ok(warp::reply::json(&response))
 }
 
+/// List the available syntax highlighting themes.
+/// `GET /v1/highlight/themes`
+async fn themes_handler(ctx: Context) -> Result<impl Reply, Rejection> {
+    let response = json!({
+        "default": ctx.theme,
+        "themes": ctx.highlighter.themes(),
+    });
+    Ok(warp::reply::json(&response))
+}
+
+/// Get the stylesheet of a theme, for use with classed highlighting.
+/// `GET /v1/highlight/themes/:name.css`
+async fn theme_css_handler(ctx: Context, name: String) -> Result<impl Reply, Rejection> {
+    // Theme names may contain spaces and parentheses, eg. `Solarized (dark)`.
+    let name = percent_encoding::percent_decode_str(&name)
+        .decode_utf8()
+        .map_err(|_| warp::reject::not_found())?;
+    let css = name
+        .strip_suffix(".css")
+        .and_then(|name| ctx.highlighter.css(name))
+        .ok_or_else(warp::reject::not_found)?;
+
+    Ok(warp::reply::with_header(
+        warp::reply::with_header(css, "Content-Type", "text/css"),
+        "Cache-Control",
+        "public, max-age=86400",
+    ))
+}
+
 async fn recover(err: Rejection) -> Result<impl Reply, std::convert::Infallible> {
     let status = if err.is_not_found() {
         StatusCode::NOT_FOUND
     } else if let Some(Error::NotFound) = err.find::<Error>() {
         StatusCode::NOT_FOUND
+    } else if let Some(Error::Unauthorized(_) | Error::AuthorizationScheme) = err.find::<Error>() {
+        StatusCode::UNAUTHORIZED
     } else {
         // Log the non-standard errors.
         tracing::error!("Error: {:?}", err);
@@ -163,38 +539,110 @@ async fn recover(err: Rejection) -> Result<impl Reply, std::convert::Infallible>
         .body(body.to_string()))
 }
 
-/// Combination of all source filters.
+/// Combination of all source filters. Projects denied by the seed's policy are not found.
 fn filters(ctx: Context) -> BoxedFilter<(impl Reply,)> {
-    project_root_filter(ctx.clone())
+    let routes = project_root_filter(ctx.clone())
         .or(commit_filter(ctx.clone()))
+        .or(notes_filter(ctx.clone()))
+        .or(project_batch_filter(ctx.clone()))
         .or(history_filter(ctx.clone()))
+        .or(file_history_filter(ctx.clone()))
+        .or(commits_feed_filter(ctx.clone()))
+        .or(project_activity_filter(ctx.clone()))
+        .or(badge_filter(ctx.clone()))
         .or(project_urn_filter(ctx.clone()))
         .or(project_alias_filter(ctx.clone()))
         .or(tree_filter(ctx.clone()))
         .or(remotes_filter(ctx.clone()))
+        .or(forks_filter(ctx.clone()))
+        .or(graph_filter(ctx.clone()))
+        .or(patches_filter(ctx.clone()))
+        .or(patch_filter(ctx.clone()))
+        .or(issues_filter(ctx.clone()))
+        .or(issue_filter(ctx.clone()))
         .or(remote_filter(ctx.clone()))
         .or(blob_filter(ctx.clone()))
-        .or(readme_filter(ctx))
-        .boxed()
+        .or(raw_filter(ctx.clone()))
+        .or(readme_filter(ctx.clone()));
+
+    policy_filter(ctx).and(routes).boxed()
+}
+
+/// Reject requests for projects denied by the seed's policy, or private to other readers, as
+/// if they didn't exist. Projects given by alias are checked when the alias is resolved.
+fn policy_filter(ctx: Context) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    reader_filter(ctx.clone())
+        .and(path::peek())
+        .and_then(move |reader: Option<PeerId>, peek: path::Peek| {
+            let ctx = ctx.clone();
+            async move {
+                match peek.segments().next().map(Urn::from_str) {
+                    Some(Ok(urn)) if !ctx.can_read(&urn, reader.as_ref()) => {
+                        Err(warp::reject::not_found())
+                    }
+                    _ => Ok::<_, Rejection>(()),
+                }
+            }
+        })
+        .untuple_one()
+}
+
+/// Authenticate the reader of a request, given an `Authorization: Bearer <token>` header.
+/// Requests without the header are unauthenticated, while invalid tokens are rejected.
+/// See [`shared::auth`].
+fn reader_filter(
+    ctx: Context,
+) -> impl Filter<Extract = (Option<PeerId>,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("authorization").and_then(move |header: Option<String>| {
+        let seed = ctx.peer_id;
+        async move {
+            let header = match header {
+                Some(header) => header,
+                None => return Ok(None),
+            };
+            let token = header
+                .strip_prefix("Bearer ")
+                .ok_or(Error::AuthorizationScheme)?;
+            let peer = shared::auth::verify(token, &seed).map_err(Error::from)?;
+
+            Ok::<_, Rejection>(Some(peer))
+        }
+    })
 }
 
-/// `GET /:project/blob/:sha/:path`
+/// `GET /:project/blob/:sha/:path?peer=<peer>&highlight=<bool>&theme=<theme>&mode=<mode>&preview=<bool>`
 fn blob_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     #[derive(serde::Deserialize)]
     struct Query {
-        highlight: bool,
+        /// Embed a preview of image blobs.
+        #[serde(default)]
+        preview: bool,
     }
 
     warp::get()
         .map(move || ctx.clone())
         .and(path::param::<Urn>())
         .and(path("blob"))
-        .and(path::param::<One>())
-        .and(warp::query().map(|q: Query| q.highlight))
+        .and(path::param::<Revision>())
+        .and(query::<PeerQueryString>())
+        .and(query::<HighlightQueryString>())
+        .and(warp::query().map(|q: Query| q.preview))
         .and(path::tail())
         .and_then(blob_handler)
 }
 
+/// `GET /:project/raw/:sha/:path?peer=<peer>`
+fn raw_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .map(move || ctx.clone())
+        .and(path::param::<Urn>())
+        .and(path("raw"))
+        .and(path::param::<Revision>())
+        .and(query::<PeerQueryString>())
+        .and(path::tail())
+        .and_then(raw_handler)
+}
+
 /// `GET /:project/remotes`
 fn remotes_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::get()
@@ -205,6 +653,81 @@ fn remotes_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Rej
         .and_then(remotes_handler)
 }
 
+/// `GET /:project/forks`
+fn forks_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .map(move || ctx.clone())
+        .and(path::param::<Urn>())
+        .and(path("forks"))
+        .and(path::end())
+        .and_then(forks_handler)
+}
+
+/// `GET /:project/graph?refs=<refs>&limit=<count>&since=<time>&until=<time>`
+fn graph_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .map(move || ctx.clone())
+        .and(path::param::<Urn>())
+        .and(path("graph"))
+        .and(path::end())
+        .and(query::<GraphQueryString>())
+        .and_then(graph_handler)
+}
+
+/// `GET /:project/patches?status=<status>`
+fn patches_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    #[derive(serde::Deserialize)]
+    struct Query {
+        status: Option<patches::Status>,
+    }
+
+    warp::get()
+        .map(move || ctx.clone())
+        .and(path::param::<Urn>())
+        .and(path("patches"))
+        .and(path::end())
+        .and(warp::query().map(|q: Query| q.status))
+        .and_then(patches_handler)
+}
+
+/// `GET /:project/patches/:peer/:branch`
+fn patch_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .map(move || ctx.clone())
+        .and(path::param::<Urn>())
+        .and(path("patches"))
+        .and(path::param::<PeerId>())
+        .and(path::tail())
+        .and_then(patch_handler)
+}
+
+/// `GET /:project/issues?state=<state>`
+fn issues_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    #[derive(serde::Deserialize)]
+    struct Query {
+        state: Option<issues::State>,
+    }
+
+    warp::get()
+        .map(move || ctx.clone())
+        .and(path::param::<Urn>())
+        .and(path("issues"))
+        .and(path::end())
+        .and(warp::query().map(|q: Query| q.state))
+        .and_then(issues_handler)
+}
+
+/// `GET /:project/issues/:id`
+fn issue_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .map(move || ctx.clone())
+        .and(path::param::<Urn>())
+        .and(path("issues"))
+        .and(path::param::<git2::Oid>())
+        .and(path::end())
+        .and_then(issue_handler)
+}
+
 /// `GET /:project/remotes/:peer`
 fn remote_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::get()
@@ -216,36 +739,96 @@ fn remote_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Reje
         .and_then(remote_handler)
 }
 
-/// `GET /:project/commits?from=<sha>`
+/// `GET /:project/commits?parent=<sha>&peer=<peer>&author=<author>&grep=<pattern>&path=<paths>&details=<bool>`
+///
+/// Also accepts `page` and `perPage`.
 fn history_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::get()
         .map(move || ctx.clone())
         .and(path::param::<Urn>())
         .and(path("commits"))
         .and(query::<CommitsQueryString>())
+        .and(query::<PageQueryString>())
         .and(path::end())
         .and_then(history_handler)
 }
 
-/// `GET /:project/commits/:sha`
+/// `GET /:project/history/:sha/:path?peer=<peer>&page=<page>&perPage=<count>`
+fn file_history_filter(
+    ctx: Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .map(move || ctx.clone())
+        .and(path::param::<Urn>())
+        .and(path("history"))
+        .and(path::param::<Revision>())
+        .and(query::<PeerQueryString>())
+        .and(query::<PageQueryString>())
+        .and(path::tail())
+        .and_then(file_history_handler)
+}
+
+/// `GET /:project/commits.atom?ref=<ref>&peer=<peer>`
+fn commits_feed_filter(
+    ctx: Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .map(move || ctx.clone())
+        .and(path::param::<Urn>())
+        .and(path("commits.atom"))
+        .and(path::end())
+        .and(query::<FeedQueryString>())
+        .and(warp::header::optional::<String>("host"))
+        .and_then(commits_feed_handler)
+}
+
+/// `GET /:project/activity/log?page=<page>&perPage=<count>`
+fn project_activity_filter(
+    ctx: Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .map(move || ctx.clone())
+        .and(path::param::<Urn>())
+        .and(path("activity"))
+        .and(path("log"))
+        .and(path::end())
+        .and(query::<PageQueryString>())
+        .and_then(project_activity_handler)
+}
+
+/// `GET /:project/commits/:sha?peer=<peer>`
 fn commit_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::get()
         .map(move || ctx.clone())
         .and(path::param::<Urn>())
         .and(path("commits"))
-        .and(path::param::<One>())
+        .and(path::param::<Revision>())
+        .and(query::<PeerQueryString>())
         .and(path::end())
         .and_then(commit_handler)
 }
 
-/// `GET /:project/readme/:sha`
+/// `GET /:project/notes/:sha?peer=<peer>`
+fn notes_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .map(move || ctx.clone())
+        .and(path::param::<Urn>())
+        .and(path("notes"))
+        .and(path::param::<Revision>())
+        .and(query::<PeerQueryString>())
+        .and(path::end())
+        .and_then(notes_handler)
+}
+
+/// `GET /:project/readme/:sha/:path?peer=<peer>`
 fn readme_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::get()
         .map(move || ctx.clone())
         .and(path::param::<Urn>())
         .and(path("readme"))
-        .and(path::param::<One>())
-        .and(path::end())
+        .and(path::param::<Revision>())
+        .and(query::<PeerQueryString>())
+        .and(path::tail())
         .and_then(readme_handler)
 }
 
@@ -253,13 +836,34 @@ fn readme_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Reje
 fn project_root_filter(
     ctx: Context,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let reader = reader_filter(ctx.clone());
+
     warp::get()
         .map(move || ctx.clone())
         .and(path::end())
+        .and(reader)
+        .and(query::<ProjectsQueryString>())
+        .and(query::<PageQueryString>())
         .and_then(project_root_handler)
         .boxed()
 }
 
+/// `POST /batch`
+fn project_batch_filter(
+    ctx: Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let reader = reader_filter(ctx.clone());
+
+    warp::post()
+        .map(move || ctx.clone())
+        .and(path("batch"))
+        .and(path::end())
+        .and(reader)
+        .and(warp::body::content_length_limit(MAX_BATCH_BODY))
+        .and(warp::body::json())
+        .and_then(project_batch_handler)
+}
+
 /// `GET /:project-urn`
 fn project_urn_filter(
     ctx: Context,
@@ -276,21 +880,39 @@ fn project_urn_filter(
 fn project_alias_filter(
     ctx: Context,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let reader = reader_filter(ctx.clone());
+
     warp::get()
         .map(move || ctx.clone())
         .and(path::param::<String>())
         .and(path::end())
+        .and(reader)
         .and_then(project_alias_handler)
         .boxed()
 }
 
-/// `GET /:project/tree/:prefix`
+/// `GET /:project/badge.svg?label=<label>&value=<value>`, where `:project` is a URN or alias.
+fn badge_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let reader = reader_filter(ctx.clone());
+
+    warp::get()
+        .map(move || ctx.clone())
+        .and(path::param::<String>())
+        .and(path("badge.svg"))
+        .and(path::end())
+        .and(reader)
+        .and(query::<BadgeQueryString>())
+        .and_then(badge_handler)
+}
+
+/// `GET /:project/tree/:sha/:prefix?peer=<peer>`
 fn tree_filter(ctx: Context) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::get()
         .map(move || ctx.clone())
         .and(path::param::<Urn>())
         .and(path("tree"))
-        .and(path::param::<One>())
+        .and(path::param::<Revision>())
+        .and(query::<PeerQueryString>())
         .and(path::tail())
         .and_then(tree_handler)
 }
@@ -316,6 +938,21 @@ async fn root_handler() -> Result<impl Reply, Rejection> {
                 "href": "/v1/delegates/:urn/projects",
                 "rel": "projects",
                 "type": "GET"
+            },
+            {
+                "href": "/v1/peers/:peer/projects",
+                "rel": "projects",
+                "type": "GET"
+            },
+            {
+                "href": "/v1/aliases",
+                "rel": "aliases",
+                "type": "GET"
+            },
+            {
+                "href": "/v1/auth/challenge",
+                "rel": "auth",
+                "type": "GET"
             }
         ]
     });
@@ -325,22 +962,96 @@ async fn root_handler() -> Result<impl Reply, Rejection> {
 async fn blob_handler(
     ctx: Context,
     project: Urn,
-    sha: One,
-    highlight: bool,
+    revision: Revision,
+    qs: PeerQueryString,
+    hl: HighlightQueryString,
+    preview: bool,
     path: warp::filters::path::Tail,
 ) -> Result<impl Reply, Rejection> {
-    let theme = if highlight {
-        Some(ctx.theme.as_str())
+    let mode = hl.mode.unwrap_or_default();
+    // The theme is only used in inline mode, in classed mode styles come from the theme CSS.
+    let inline = if hl.highlight && mode == highlight::Mode::Inline {
+        let theme = hl.theme.unwrap_or_else(|| ctx.theme.clone());
+        if !ctx.highlighter.has_theme(&theme) {
+            return Err(Error::UnknownTheme(theme).into());
+        }
+        Some(theme)
     } else {
         None
     };
-    let reference = Reference::head(Namespace::from(project), None, sha);
-    let blob = browse(reference, ctx.paths, |browser| {
-        radicle_source::blob::highlighting::blob::<PeerId>(browser, None, path.as_str(), theme)
+    // Git objects are not `Send`, and must be dropped before browsing the source.
+    let (commit, meta) = {
+        let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+        let commit = revision.resolve(&repo, &project, qs.peer.as_ref())?;
+        let blob = find_blob(&repo, commit, path.as_str())?;
+        let mime = mime_guess::from_path(path.as_str()).first_or_octet_stream();
+        let meta = json!({
+            "path": path.as_str(),
+            "oid": blob.id().to_string(),
+            "size": blob.size(),
+            "mimeType": mime.to_string(),
+            "raw": format!("/v1/projects/{}/raw/{}/{}", project, commit, path.as_str()),
+            "commit": commit.to_string(),
+        });
+
+        // Binary and large blobs are never highlighted. For binaries, we only return metadata,
+        // the content can be fetched from the raw endpoint.
+        if blob.is_binary() {
+            let mut response = meta;
+            response["binary"] = json!(true);
+            response["html"] = json!(false);
+            response["truncated"] = json!(false);
+
+            if preview && mime.type_() == "image" && blob.size() <= ctx.preview_limit {
+                response["preview"] = json!(format!(
+                    "data:{};base64,{}",
+                    mime,
+                    base64::encode(blob.content())
+                ));
+            }
+            return Ok(warp::reply::json(&response));
+        }
+        if blob.size() > ctx.blob_limit {
+            let mut response = meta;
+            response["binary"] = json!(false);
+            response["html"] = json!(false);
+            response["truncated"] = json!(true);
+            response["content"] = json!(truncate(blob.content(), ctx.blob_limit));
+
+            return Ok(warp::reply::json(&response));
+        }
+
+        (commit, meta)
+    };
+
+    // In classed mode, we get the plain blob and highlight it ourselves.
+    let blob = browse(project, commit, ctx.paths, |browser| {
+        radicle_source::blob::highlighting::blob::<PeerId>(
+            browser,
+            None,
+            path.as_str(),
+            inline.as_deref(),
+        )
     })
     .await?;
+    let mut response = json!(&blob);
+    for (key, value) in meta.as_object().into_iter().flatten() {
+        if response.get(key).is_none() {
+            response[key] = value.clone();
+        }
+    }
+    response["truncated"] = json!(false);
+
+    if hl.highlight && mode == highlight::Mode::Classed && response["binary"] == json!(false) {
+        if let Some(content) = response["content"].as_str() {
+            let html = ctx.highlighter.classed(path.as_str(), content);
+
+            response["content"] = json!(html);
+            response["html"] = json!(true);
+        }
+    }
 
-    Ok(warp::reply::json(&blob))
+    Ok(warp::reply::json(&response))
 }
 
 async fn remotes_handler(ctx: Context, urn: Urn) -> Result<impl Reply, Rejection> {
@@ -357,19 +1068,15 @@ async fn remotes_handler(ctx: Context, urn: Urn) -> Result<impl Reply, Rejection
         .into_iter()
         .filter_map(|t| t.peer_id())
         .map(|peer| -> Result<serde_json::Value, Rejection> {
-            if let Ok(delegate_urn) = Urn::try_from(Reference::rad_self(
-                Namespace::from(urn.clone()),
-                Some(peer),
-            )) {
-                if let Ok(Some(person)) = identities::person::get(&storage, &delegate_urn) {
-                    let delegate = meta.delegates.iter().any(|d| d.contains(&peer));
-
-                    return Ok(json!({
-                        "id": peer,
-                        "name": person.subject().name.to_string(),
-                        "delegate": delegate
-                    }));
-                }
+            if let Some(person) = get_person(&storage, &urn, peer) {
+                let delegate = meta.delegates.iter().any(|d| d.contains(&peer));
+
+                return Ok(json!({
+                    "id": peer,
+                    "name": person.subject().name.to_string(),
+                    "delegate": delegate,
+                    "extensions": payload::extensions(person.payload(), payload::PERSON_NAMESPACE),
+                }));
             }
             Ok(json!({ "id": peer }))
         })
@@ -378,22 +1085,387 @@ async fn remotes_handler(ctx: Context, urn: Urn) -> Result<impl Reply, Rejection
     Ok(warp::reply::json(&response))
 }
 
-async fn remote_handler(
+/// List the branches of each tracked peer, with their divergence from the canonical head.
+async fn forks_handler(ctx: Context, urn: Urn) -> Result<impl Reply, Rejection> {
+    let storage = ReadOnly::open(&ctx.paths).map_err(Error::from)?;
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+    let project = identities::project::get(&storage, &urn)
+        .map_err(Error::Identities)?
+        .ok_or(Error::NotFound)?;
+    let meta: project::Metadata = project.try_into()?;
+    let info = get_info(&repo, meta, ctx.quorum);
+    let base = info.canonical_head();
+    let tracked = tracking::tracked(&storage, Some(&urn))
+        .map_err(|_| Error::NotFound)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Error::from)?;
+
+    let mut forks = Vec::new();
+    for peer in tracked.into_iter().filter_map(|t| t.peer_id()) {
+        let branches = refs::remote_branches(&repo, &urn, &peer)?
+            .into_iter()
+            .map(|(name, head)| forks::Branch::new(&repo, name, head, base))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Error::from)?;
+
+        forks.push(forks::Fork {
+            id: peer,
+            name: get_person_name(&storage, &urn, peer),
+            delegate: info.meta.delegates.iter().any(|d| d.contains(&peer)),
+            branches,
+        });
+    }
+    // Delegates first, then most recently updated forks.
+    forks.sort_by_key(|f| (!f.delegate, std::cmp::Reverse(f.timestamp())));
+
+    let response = json!({
+        "defaultBranch": &info.meta.default_branch,
+        "base": base.map(|b| b.to_string()),
+        "forks": forks,
+    });
+
+    Ok(warp::reply::json(&response))
+}
+
+/// Get the commit graph of a project, from the given refs or from all local and delegate
+/// branches.
+async fn graph_handler(
     ctx: Context,
-    project: Urn,
-    peer_id: PeerId,
+    urn: Urn,
+    qs: GraphQueryString,
 ) -> Result<impl Reply, Rejection> {
+    let info = project_info(urn.clone(), &ctx)?;
     let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
-    // This is necessary to get any references to show up in the later calls. Go figure.
-    let _ = repo.references().map_err(Error::from)?;
 
-    let namespace = project.encode_id();
-    let remote = peer_id.default_encoding();
+    let mut refs = Vec::new();
+    match qs.refs {
+        Some(names) => {
+            for name in names.split(',').filter(|n| !n.is_empty()) {
+                let oid = Revision::from(name.to_owned()).resolve(&repo, &urn, None)?;
 
-    repo.set_namespace(&namespace).map_err(Error::from)?;
+                refs.push((name.to_owned(), oid));
+            }
+        }
+        None => {
+            refs.extend(refs::local_branches(&repo, &urn)?);
 
-    let branches = RepositoryRef::from(&repo)
-        .list_branches(git::RefScope::Remote { name: Some(remote) })
+            for peer in info.meta.delegates.iter().flat_map(|d| d.ids()) {
+                for (branch, oid) in refs::remote_branches(&repo, &urn, &peer)? {
+                    refs.push((format!("{}/{}", peer, branch), oid));
+                }
+            }
+        }
+    }
+    let bounds = graph::Bounds {
+        limit: qs
+            .limit
+            .unwrap_or(GraphQueryString::LIMIT)
+            .min(GraphQueryString::MAX_LIMIT),
+        since: qs.since,
+        until: qs.until,
+    };
+    let graph = graph::Graph::build(&repo, &refs, bounds).map_err(Error::from)?;
+
+    Ok(warp::reply::json(&graph))
+}
+
+/// List the branches of non-delegate peers as patches against the canonical head.
+async fn patches_handler(
+    ctx: Context,
+    urn: Urn,
+    status: Option<patches::Status>,
+) -> Result<impl Reply, Rejection> {
+    let info = project_info(urn.clone(), &ctx)?;
+    let base = info.canonical_head().ok_or(Error::MissingLocalState)?;
+    let storage = ReadOnly::open(&ctx.paths).map_err(Error::from)?;
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+    let tracked = tracking::tracked(&storage, Some(&urn))
+        .map_err(|_| Error::NotFound)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Error::from)?;
+
+    let mut patches = Vec::new();
+    for peer in tracked.into_iter().filter_map(|t| t.peer_id()) {
+        if info.meta.delegates.iter().any(|d| d.contains(&peer)) {
+            continue;
+        }
+        for (branch, head) in refs::remote_branches(&repo, &urn, &peer)? {
+            // A peer's default branch is their copy of the project, not a proposal.
+            if branch == info.meta.default_branch {
+                continue;
+            }
+            if let Some(patch) =
+                patches::Patch::load(&repo, peer, branch, head, base).map_err(Error::from)?
+            {
+                if status.is_none_or(|s| s == patch.status) {
+                    patches.push(patch);
+                }
+            }
+        }
+    }
+    patches.sort_by_key(|p| std::cmp::Reverse(p.timestamp));
+
+    Ok(warp::reply::json(&patches))
+}
+
+/// Get a single patch, ie. a peer's branch compared to the canonical head.
+async fn patch_handler(
+    ctx: Context,
+    urn: Urn,
+    peer: PeerId,
+    branch: warp::filters::path::Tail,
+) -> Result<impl Reply, Rejection> {
+    let info = project_info(urn.clone(), &ctx)?;
+    let base = info.canonical_head().ok_or(Error::MissingLocalState)?;
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+    let head = refs::remote_branch(&repo, &urn, &peer, branch.as_str())?;
+    let patch = patches::Patch::load(&repo, peer, branch.as_str().to_owned(), head, base)
+        .map_err(Error::from)?
+        .ok_or(Error::NotFound)?;
+
+    Ok(warp::reply::json(&patch))
+}
+
+/// List the issues published by tracked peers, most recently updated first.
+async fn issues_handler(
+    ctx: Context,
+    urn: Urn,
+    state: Option<issues::State>,
+) -> Result<impl Reply, Rejection> {
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+    let peers = get_tracked_peers(&ctx, &urn)?;
+
+    let mut result = Vec::new();
+    for (id, tips) in issues::tips(&repo, &urn, &peers).map_err(Error::from)? {
+        if let Some(issue) = issues::Issue::load(&repo, id, &tips).map_err(Error::from)? {
+            if state.is_none_or(|s| s == issue.state) {
+                result.push(issue);
+            }
+        }
+    }
+    result.sort_by_key(|i| std::cmp::Reverse(i.updated));
+
+    Ok(warp::reply::json(&result))
+}
+
+/// Get a single issue, merging the changes published by all tracked peers.
+async fn issue_handler(ctx: Context, urn: Urn, id: git2::Oid) -> Result<impl Reply, Rejection> {
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+    let peers = get_tracked_peers(&ctx, &urn)?;
+    let tips = issues::tips(&repo, &urn, &peers)
+        .map_err(Error::from)?
+        .remove(&id)
+        .ok_or(Error::NotFound)?;
+    let issue = issues::Issue::load(&repo, id, &tips)
+        .map_err(Error::from)?
+        .ok_or(Error::NotFound)?;
+
+    Ok(warp::reply::json(&issue))
+}
+
+/// List the commits that touched a file, following renames.
+async fn file_history_handler(
+    ctx: Context,
+    project: Urn,
+    revision: Revision,
+    qs: PeerQueryString,
+    page: PageQueryString,
+    path: warp::filters::path::Tail,
+) -> Result<impl Reply, Rejection> {
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+    let commit = revision.resolve(&repo, &project, qs.peer.as_ref())?;
+    let skip = page.page().saturating_mul(page.per_page());
+    // Look for one more entry than needed, to know whether there are more pages.
+    let limit = skip.saturating_add(page.per_page()).saturating_add(1);
+    let entries =
+        history::file_history(&repo, commit, path.as_str(), Some(limit)).map_err(Error::from)?;
+
+    if entries.is_empty() {
+        return Err(Error::NotFound.into());
+    }
+    let more = entries.len() == limit;
+    let entries = entries
+        .into_iter()
+        .skip(skip)
+        .take(page.per_page())
+        .collect::<Vec<_>>();
+    let response = json!({
+        "items": entries,
+        "page": page.page(),
+        "perPage": page.per_page(),
+        "hasMore": more,
+        "path": path.as_str(),
+        "commit": commit.to_string(),
+    });
+
+    Ok(warp::reply::json(&response))
+}
+
+/// Feed of the latest commits of a branch, by default the project's default branch.
+async fn commits_feed_handler(
+    ctx: Context,
+    urn: Urn,
+    query: FeedQueryString,
+    host: Option<String>,
+) -> Result<impl Reply, Rejection> {
+    let info = project_info(urn.clone(), &ctx)?;
+    let (head, name) = match query.revision {
+        Some(name) => {
+            let revision = Revision::from(name.clone());
+
+            (
+                resolve(&ctx.paths, &urn, &revision, query.peer.as_ref())?,
+                name,
+            )
+        }
+        None => (
+            info.canonical_head().ok_or(Error::MissingLocalState)?,
+            info.meta.default_branch.clone(),
+        ),
+    };
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+    let base = base_url(&ctx, host);
+
+    let mut walk = repo.revwalk().map_err(Error::from)?;
+    walk.push(head).map_err(Error::from)?;
+
+    let mut entries = Vec::new();
+    for oid in walk.take(feed::FEED_LENGTH) {
+        let commit = repo
+            .find_commit(oid.map_err(Error::from)?)
+            .map_err(Error::from)?;
+        let summary = commit.summary().unwrap_or_default().to_owned();
+
+        entries.push(feed_entry(&ctx, &base, &urn, &commit, summary));
+    }
+    let feed = feed::Feed {
+        id: format!("{}/v1/projects/{}/commits.atom", base, urn),
+        title: format!("{} commits on {}", info.meta.name, name),
+        updated: entries.first().map(|e| e.updated).unwrap_or_default(),
+        entries,
+    };
+
+    Ok(atom(feed))
+}
+
+/// Feed of the latest branch updates across all projects.
+async fn activity_feed_handler(
+    ctx: Context,
+    reader: Option<PeerId>,
+    host: Option<String>,
+) -> Result<impl Reply, Rejection> {
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+    let base = base_url(&ctx, host);
+
+    let mut events = Vec::new();
+    for project in get_projects(&ctx, reader.as_ref()) {
+        // Only the most recent events of each project can make it into the feed.
+        let recent = activity::events(&repo, &project.urn, Some(feed::FEED_LENGTH));
+
+        for event in recent.map_err(Error::from)? {
+            if event.branch().is_some() {
+                events.push((project.name.clone(), event));
+            }
+        }
+    }
+    events.sort_by_key(|(_, e)| std::cmp::Reverse(e.timestamp));
+
+    let mut entries = Vec::new();
+    for (name, event) in events.into_iter().take(feed::FEED_LENGTH) {
+        // Skip branch deletions and updates to commits we no longer have.
+        let commit = match repo.find_commit(event.new) {
+            Ok(commit) => commit,
+            Err(_) => continue,
+        };
+        let title = format!(
+            "{}: {} updated to {}",
+            name,
+            event.branch().unwrap_or(&event.refname),
+            &event.new.to_string()[..7]
+        );
+        let mut entry = feed_entry(&ctx, &base, &event.urn, &commit, title);
+
+        // The same commit can be pushed to several branches, or several times.
+        entry.id = format!("{}#{}@{}", entry.id, event.refname, event.timestamp);
+        entry.updated = event.timestamp;
+        entries.push(entry);
+    }
+    let feed = feed::Feed {
+        id: format!("{}/v1/activity.atom", base),
+        title: String::from("Activity"),
+        updated: entries.first().map(|e| e.updated).unwrap_or_default(),
+        entries,
+    };
+
+    Ok(atom(feed))
+}
+
+/// List the activity of all projects, most recent first.
+async fn activity_handler(
+    ctx: Context,
+    reader: Option<PeerId>,
+    page: PageQueryString,
+) -> Result<impl Reply, Rejection> {
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+
+    let skip = page.page().saturating_mul(page.per_page());
+    // Only the most recent events of each project can be on the requested page. Look for one
+    // more event than needed, to know whether there are more pages.
+    let limit = skip.saturating_add(page.per_page()).saturating_add(1);
+
+    let mut events = Vec::new();
+    for project in get_projects(&ctx, reader.as_ref()) {
+        events.extend(activity::events(&repo, &project.urn, Some(limit)).map_err(Error::from)?);
+    }
+    events.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+
+    let more = events.len() >= limit;
+    let events = events
+        .into_iter()
+        .skip(skip)
+        .take(page.per_page())
+        .collect::<Vec<_>>();
+    let response = json!({
+        "items": events,
+        "page": page.page(),
+        "perPage": page.per_page(),
+        "hasMore": more,
+    });
+
+    Ok(warp::reply::json(&response))
+}
+
+/// List the activity of a project, most recent first.
+async fn project_activity_handler(
+    ctx: Context,
+    urn: Urn,
+    page: PageQueryString,
+) -> Result<impl Reply, Rejection> {
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+    // Make sure the project exists.
+    project_info(urn.clone(), &ctx)?;
+
+    let events = activity::events(&repo, &urn, None).map_err(Error::from)?;
+
+    Ok(warp::reply::json(&page.paginate(events)))
+}
+
+async fn remote_handler(
+    ctx: Context,
+    project: Urn,
+    peer_id: PeerId,
+) -> Result<impl Reply, Rejection> {
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+    // This is necessary to get any references to show up in the later calls. Go figure.
+    let _ = repo.references().map_err(Error::from)?;
+
+    let namespace = project.encode_id();
+    let remote = peer_id.default_encoding();
+
+    repo.set_namespace(&namespace).map_err(Error::from)?;
+
+    let branches = RepositoryRef::from(&repo)
+        .list_branches(git::RefScope::Remote { name: Some(remote) })
         .map_err(Error::from)?
         .iter()
         .filter(|branch| !branch.name.to_string().starts_with("rad/"))
@@ -419,58 +1491,116 @@ async fn history_handler(
     ctx: Context,
     project: Urn,
     qs: CommitsQueryString,
+    page: PageQueryString,
 ) -> Result<impl Reply, Rejection> {
     let CommitsQueryString {
         since,
         until,
         parent,
+        peer,
+        author,
+        grep,
+        path,
+        details,
+        stats,
     } = qs;
+    let details = details.unwrap_or_else(|| page.is_requested());
+    let stats = stats.unwrap_or_else(|| !page.is_requested());
 
-    let (sha, fallback_to_head) = match parent {
-        Some(commit) => (commit, false),
+    let (revision, fallback_to_head) = match parent {
+        Some(commit) => (Revision::from(commit), false),
         None => {
-            let meta = project_info(project.to_owned(), ctx.paths.to_owned())?;
-            (meta.head.to_string(), true)
+            let meta = project_info(project.to_owned(), &ctx)?;
+            let head = meta.head.ok_or(Error::MissingLocalState)?;
+
+            (Revision::from(head.to_string()), true)
         }
     };
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+    let commit = revision.resolve(&repo, &project, peer.as_ref())?;
 
-    let reference = Reference::head(
-        Namespace::from(project),
-        None,
-        One::from_str(&sha).map_err(|_| Error::NotFound)?,
-    );
-
-    let commits = browse(reference, ctx.paths, |browser| {
-        let mut result = radicle_source::commits::<PeerId>(browser, None)?;
-        let headers: Vec<Header> = result
-            .headers
+    let author = author.map(|author| match PeerId::from_str(&author) {
+        Ok(peer) => commits::Author::Key(shared::ssh::peer_fingerprint(&peer)),
+        Err(_) => commits::Author::Pattern(author),
+    });
+    let grep = grep
+        .map(|pattern| {
+            regex::RegexBuilder::new(&pattern)
+                .case_insensitive(true)
+                .build()
+        })
+        .transpose()
+        .map_err(Error::from)?;
+    let filter = commits::Filter {
+        author,
+        grep,
+        paths: path
             .iter()
-            .filter(|q| {
-                if let (Some(since), Some(until)) = (since, until) {
-                    q.committer_time.seconds() >= since && q.committer_time.seconds() < until
-                } else if let Some(since) = since {
-                    q.committer_time.seconds() >= since
-                } else if let Some(until) = until {
-                    q.committer_time.seconds() < until
-                } else {
-                    // If neither `since` nor `until` are specified, we include the commit.
-                    true
-                }
-            })
-            .cloned()
-            .collect();
+            .flat_map(|p| p.split(','))
+            .filter(|p| !p.is_empty())
+            .map(|p| p.to_owned())
+            .collect(),
+        since,
+        until,
+    };
 
-        result.headers = headers;
-        // Since the headers filtering can alter the amount of commits we have to recalculate it here.
-        result.stats.commits = result.headers.len();
+    let (skip, take) = if page.is_requested() {
+        (
+            page.page().saturating_mul(page.per_page()),
+            Some(page.per_page()),
+        )
+    } else {
+        (0, None)
+    };
+    let walk = commits::walk(&repo, commit, &filter, skip, take, stats).map_err(Error::from)?;
+    let signers = if details {
+        get_signers(&ctx, &project)?
+    } else {
+        Vec::new()
+    };
+    let mut headers = Vec::new();
+    for oid in walk.commits.iter().copied() {
+        let commit = repo.find_commit(oid).map_err(Error::from)?;
+        let commit = git::Commit::try_from(commit).map_err(Error::from)?;
+        let mut header = json!(Header::from(&commit));
+
+        if details {
+            let signature =
+                signature::Signature::load(&repo, oid, &signers).map_err(Error::from)?;
+            let notes = notes::notes(&repo, &project, peer.as_ref(), &ctx.notes_refs, oid)
+                .map_err(Error::from)?;
+
+            header["signature"] = json!(signature);
+            header["notes"] = json!(notes);
+        }
+        headers.push(header);
+    }
 
-        Ok(result)
-    })
-    .await?;
-    let response = json!({
-        "headers": &commits.headers,
-        "stats": &commits.stats,
+    let mut response = json!({
+        "headers": &headers,
+        "commit": commit.to_string(),
     });
+    if page.is_requested() {
+        response["page"] = json!(page.page());
+        response["perPage"] = json!(page.per_page());
+        response["hasMore"] = json!(walk.more);
+    }
+    // Stats are about all matching commits, not only the requested page.
+    if let Some(stats) = walk.stats {
+        let branches = repo
+            .references_glob(&format!(
+                "refs/namespaces/{}/refs/heads/*",
+                project.encode_id()
+            ))
+            .map_err(Error::from)?
+            .count();
+
+        response["stats"] = json!({
+            "branches": branches,
+            "commits": stats.commits,
+            "contributors": stats.contributors.len(),
+        });
+    }
 
     if fallback_to_head {
         return Ok(warp::reply::with_status(
@@ -485,103 +1615,317 @@ async fn history_handler(
     ))
 }
 
-async fn commit_handler(ctx: Context, project: Urn, sha: One) -> Result<impl Reply, Rejection> {
-    let reference = Reference::head(Namespace::from(project), None, sha.to_owned());
-    let commit = browse(reference, ctx.paths, |browser| {
-        let oid = browser.oid(&sha)?;
+async fn commit_handler(
+    ctx: Context,
+    project: Urn,
+    revision: Revision,
+    qs: PeerQueryString,
+) -> Result<impl Reply, Rejection> {
+    let oid = resolve(&ctx.paths, &project, &revision, qs.peer.as_ref())?;
+    let commit = browse(project, oid, ctx.paths, |browser| {
         radicle_source::commit(browser, oid)
     })
     .await?;
-    let response = json!(&commit);
+    let signers = get_signers(&ctx, &project)?;
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+    let signature = signature::Signature::load(&repo, oid, &signers).map_err(Error::from)?;
+    let notes = notes::notes(&repo, &project, qs.peer.as_ref(), &ctx.notes_refs, oid)
+        .map_err(Error::from)?;
+    let mut response = json!(&commit);
+    response["header"]["signature"] = json!(signature);
+    response["header"]["notes"] = json!(notes);
 
     Ok(warp::reply::json(&response))
 }
 
-async fn readme_handler(ctx: Context, project: Urn, sha: One) -> Result<impl Reply, Rejection> {
-    let reference = Reference::head(Namespace::from(project), None, sha);
-    let paths = &[
-        "README",
-        "README.md",
-        "README.markdown",
-        "README.txt",
-        "README.rst",
-        "Readme.md",
-    ];
-    let blob = browse(reference, ctx.paths, |browser| {
-        for path in paths {
-            if let Ok(blob) =
-                radicle_source::blob::highlighting::blob::<PeerId>(browser, None, path, None)
-            {
-                return Ok(blob);
-            }
-        }
-        Err(radicle_source::Error::PathNotFound(
-            Path::try_from("README").unwrap(),
-        ))
-    })
-    .await?;
+/// Get the notes on a commit.
+async fn notes_handler(
+    ctx: Context,
+    project: Urn,
+    revision: Revision,
+    qs: PeerQueryString,
+) -> Result<impl Reply, Rejection> {
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+    let oid = revision.resolve(&repo, &project, qs.peer.as_ref())?;
+    let notes = notes::notes(&repo, &project, qs.peer.as_ref(), &ctx.notes_refs, oid)
+        .map_err(Error::from)?;
 
-    Ok(warp::reply::json(&blob))
+    Ok(warp::reply::json(&json!({
+        "commit": oid.to_string(),
+        "notes": notes,
+    })))
 }
 
-/// List all projects
-async fn project_root_handler(ctx: Context) -> Result<Json, Rejection> {
-    use radicle_daemon::git::identities::SomeIdentity;
+/// Find the README of a directory, and render it to HTML when it's Markdown or reStructuredText.
+async fn readme_handler(
+    ctx: Context,
+    project: Urn,
+    revision: Revision,
+    qs: PeerQueryString,
+    path: warp::filters::path::Tail,
+) -> Result<impl Reply, Rejection> {
+    // The commit is resolved within the project namespace, like for the other source
+    // endpoints, so that READMEs of other projects can't be read through this one.
+    let commit = resolve(&ctx.paths, &project, &revision, qs.peer.as_ref())?;
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+    let readme =
+        readme::Readme::find(&repo, &project, commit, path.as_str())?.ok_or(Error::NotFound)?;
+    let mut response = json!(&readme);
+    response["commit"] = json!(commit.to_string());
 
+    Ok(warp::reply::json(&response))
+}
+
+/// Serve the raw content of a blob.
+async fn raw_handler(
+    ctx: Context,
+    project: Urn,
+    revision: Revision,
+    qs: PeerQueryString,
+    path: warp::filters::path::Tail,
+) -> Result<impl Reply, Rejection> {
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+    let commit = revision.resolve(&repo, &project, qs.peer.as_ref())?;
+    let blob = find_blob(&repo, commit, path.as_str())?;
+    let mime = mime_guess::from_path(path.as_str()).first_or_octet_stream();
+    // Blobs are pushed by anyone, so types the browser would run, eg. HTML or SVG, are
+    // served as plain text, and the response is sandboxed in any case.
+    let mime = if is_active(&mime) {
+        mime_guess::mime::TEXT_PLAIN_UTF_8.to_string()
+    } else {
+        mime.to_string()
+    };
+    let reply = warp::reply::with_header(blob.content().to_vec(), "Content-Type", mime);
+    let reply = warp::reply::with_header(reply, "X-Content-Type-Options", "nosniff");
+
+    Ok(warp::reply::with_header(
+        reply,
+        "Content-Security-Policy",
+        "sandbox",
+    ))
+}
+
+/// Whether content of the given type can run scripts when opened in a browser.
+fn is_active(mime: &mime_guess::Mime) -> bool {
+    let subtype = mime.subtype().as_str();
+    let suffix = mime.suffix().map(|s| s.as_str());
+
+    subtype == "html"
+        || subtype == "xml"
+        || subtype == "javascript"
+        || subtype == "ecmascript"
+        || suffix == Some("xml")
+}
+
+/// List projects from the project index, a page at a time.
+async fn project_root_handler(
+    ctx: Context,
+    reader: Option<PeerId>,
+    qs: ProjectsQueryString,
+    page: PageQueryString,
+) -> Result<Json, Rejection> {
+    let repo = git2::Repository::open_bare(&ctx.paths.git_dir()).map_err(Error::from)?;
     let storage = ReadOnly::open(&ctx.paths).map_err(Error::from)?;
-    let repo = git::Repository::new(&ctx.paths.git_dir()).map_err(Error::from)?;
-    let projects = identities::any::list(&storage)
-        .map_err(Error::from)?
-        .filter_map(|res| {
-            res.map(|id| match id {
-                SomeIdentity::Project(project) => {
-                    let meta: project::Metadata = project.try_into().ok()?;
-                    let head = get_head_commit(&repo, &meta.urn, &meta.default_branch).ok()?;
-
-                    Some(Info {
-                        meta,
-                        head: head.id,
+
+    let q = qs.q.map(|q| q.to_lowercase());
+    let delegate = qs.delegate.as_deref().map(|d| match PeerId::from_str(d) {
+        Ok(peer) => Either::Left(peer),
+        Err(_) => Either::Right(d),
+    });
+    let mut projects = ctx
+        .projects
+        .projects()
+        .into_iter()
+        // Projects without a default branch are left out.
+        .filter(|p| p.default_branch.is_some())
+        .filter(|p| ctx.projects.can_read(&p.urn, reader.as_ref()))
+        .filter(|p| {
+            q.as_ref().is_none_or(|q| {
+                p.name.to_lowercase().contains(q)
+                    || p.description
+                        .as_ref()
+                        .is_some_and(|d| d.to_lowercase().contains(q))
+            })
+        })
+        .filter(|p| {
+            delegate.as_ref().is_none_or(|d| {
+                p.delegations
+                    .iter()
+                    .any(|delegation| match (d, delegation) {
+                        (Either::Left(peer), Delegation::Direct(id)) => id == peer,
+                        (Either::Left(peer), Delegation::Indirect { peers, .. }) => {
+                            peers.contains(peer)
+                        }
+                        (Either::Right(urn), Delegation::Indirect { urn: u, .. }) => {
+                            u.to_string() == *urn
+                        }
+                        (Either::Right(_), Delegation::Direct(_)) => false,
                     })
-                }
-                _ => None,
             })
-            .transpose()
         })
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(Error::from)?;
+        .collect::<Vec<_>>();
 
-    Ok(warp::reply::json(&projects))
+    match qs.sort.unwrap_or_default() {
+        ProjectSort::Name => projects.sort_by(|a, b| a.name.cmp(&b.name)),
+        ProjectSort::Updated => projects.sort_by(|a, b| b.updated.cmp(&a.updated)),
+        ProjectSort::Created => projects.sort_by(|a, b| b.created.cmp(&a.created)),
+    }
+    // Projects whose identity fails to load are left out.
+    let infos = projects
+        .iter()
+        .filter_map(|p| Some((ctx.indexed_info(&repo, &storage, &p.urn).ok()?, p)))
+        .collect::<Vec<_>>();
+    let entries = infos
+        .iter()
+        .map(|(info, project)| project::Entry::new(info, project))
+        .collect::<Vec<_>>();
+
+    // Forget the info of projects that are gone.
+    ctx.infos
+        .lock()
+        .expect("info cache lock is not poisoned")
+        .retain(|urn, _| ctx.projects.contains(urn));
+
+    Ok(warp::reply::json(&page.paginate(entries)))
 }
 
 async fn project_urn_handler(ctx: Context, urn: Urn) -> Result<Json, Rejection> {
-    let info = project_info(urn, ctx.paths)?;
+    let info = project_info(urn, &ctx)?;
 
     Ok(warp::reply::json(&info))
 }
 
-async fn project_alias_handler(ctx: Context, name: String) -> Result<Json, Rejection> {
-    let mut aliases = ctx.aliases.write().await;
-    if !aliases.contains_key(&name) {
-        // If the alias does not exist, rebuild the cache.
-        ctx.populate_aliases(&mut aliases).await?;
+/// Get the info of several projects, given by URN or alias. Projects that can't be found
+/// or have no default branch are reported per item.
+async fn project_batch_handler(
+    ctx: Context,
+    reader: Option<PeerId>,
+    batch: BatchRequest,
+) -> Result<Json, Rejection> {
+    if batch.projects.len() > MAX_BATCH_SIZE {
+        return Err(Error::BatchSize(MAX_BATCH_SIZE).into());
     }
-    let urn = aliases
-        .get(&name)
-        .cloned()
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+    let storage = ReadOnly::open(&ctx.paths).map_err(Error::from)?;
+    // Resolve all items against the same state of the index, without refreshing it.
+    let resolved = {
+        let snapshot = ctx.projects.snapshot();
+
+        batch
+            .projects
+            .into_iter()
+            .map(|id| {
+                let urn = Urn::from_str(&id)
+                    .ok()
+                    .or_else(|| snapshot.resolve_alias(&id))
+                    .filter(|urn| snapshot.can_read(urn, reader.as_ref()));
+                let digest = urn.as_ref().and_then(|urn| snapshot.digest(urn));
+
+                (id, urn.zip(digest))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let items = resolved
+        .into_iter()
+        .map(|(id, resolved)| {
+            // Projects without a local default branch are returned with a `null` head.
+            let result = resolved
+                .ok_or(Error::NotFound)
+                .and_then(|(urn, digest)| ctx.cached_info(&repo, &storage, &urn, digest));
+
+            match result {
+                Ok(info) => json!({ "id": id, "project": &*info }),
+                Err(err) => json!({
+                    "id": id,
+                    "error": err.to_string(),
+                    "code": if let Error::NotFound = err {
+                        StatusCode::NOT_FOUND.as_u16()
+                    } else {
+                        StatusCode::BAD_REQUEST.as_u16()
+                    },
+                }),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Ok(warp::reply::json(&items))
+}
+
+async fn project_alias_handler(
+    ctx: Context,
+    name: String,
+    reader: Option<PeerId>,
+) -> Result<Json, Rejection> {
+    let urn = ctx
+        .projects
+        .resolve_alias(&name)
         .ok_or_else(warp::reject::not_found)?;
 
+    if !ctx.can_read(&urn, reader.as_ref()) {
+        return Err(warp::reject::not_found());
+    }
+
     project_urn_handler(ctx.clone(), urn).await
 }
 
+/// Render a badge for a project, given by URN or alias.
+async fn badge_handler(
+    ctx: Context,
+    project: String,
+    reader: Option<PeerId>,
+    qs: BadgeQueryString,
+) -> Result<impl Reply, Rejection> {
+    let urn = match Urn::from_str(&project) {
+        Ok(urn) => urn,
+        Err(_) => ctx
+            .projects
+            .resolve_alias(&project)
+            .ok_or_else(warp::reject::not_found)?,
+    };
+    if !ctx.can_read(&urn, reader.as_ref()) {
+        return Err(warp::reject::not_found());
+    }
+    let info = project_info(urn.clone(), &ctx)?;
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir()).map_err(Error::from)?;
+    let kind = qs.value.unwrap_or_default();
+
+    let value = match kind {
+        badge::Value::Commit => info
+            .canonical_head()
+            .and_then(|head| repo.find_commit(head).ok())
+            .and_then(|commit| {
+                chrono::Utc
+                    .timestamp_opt(commit.time().seconds(), 0)
+                    .single()
+            })
+            .map(|time| time.format("%Y-%m-%d").to_string()),
+        badge::Value::Delegates => Some(info.meta.delegates.len().to_string()),
+        badge::Value::Peers => Some(get_tracked_peers(&ctx, &urn)?.len().to_string()),
+        badge::Value::Tag => get_latest_tag(&repo, &urn),
+    };
+    let svg = badge::render(
+        qs.label.as_deref().unwrap_or_else(|| kind.label()),
+        value.as_deref().unwrap_or("none"),
+    );
+
+    Ok(warp::reply::with_header(
+        warp::reply::with_header(svg, "Content-Type", "image/svg+xml"),
+        "Cache-Control",
+        "public, max-age=300",
+    ))
+}
+
 /// Fetch a [`radicle_source::Tree`].
 async fn tree_handler(
     ctx: Context,
     project: Urn,
-    sha: One,
+    revision: Revision,
+    qs: PeerQueryString,
     path: warp::filters::path::Tail,
 ) -> Result<impl Reply, Rejection> {
-    let reference = Reference::head(Namespace::from(project), None, sha);
-    let (tree, stats) = browse(reference, ctx.paths, |browser| {
+    let commit = resolve(&ctx.paths, &project, &revision, qs.peer.as_ref())?;
+    let (tree, stats) = browse(project, commit, ctx.paths, |browser| {
         Ok((
             radicle_source::tree::<PeerId>(browser, None, Some(path.as_str().to_owned()))?,
             browser.get_stats()?,
@@ -593,109 +1937,379 @@ async fn tree_handler(
         "entries": &tree.entries,
         "info": &tree.info,
         "stats": &stats,
+        "commit": commit.to_string(),
     });
 
     Ok(warp::reply::json(&response))
 }
 
 /// List all projects that delegate is a part of.
-async fn delegates_projects_handler(ctx: Context, delegate: Urn) -> Result<impl Reply, Rejection> {
-    use radicle_daemon::git::identities::SomeIdentity;
-
+async fn delegates_projects_handler(
+    ctx: Context,
+    reader: Option<PeerId>,
+    delegate: Urn,
+) -> Result<impl Reply, Rejection> {
     let storage = ReadOnly::open(&ctx.paths).map_err(Error::from)?;
-    let repo = git::Repository::new(&ctx.paths.git_dir()).map_err(Error::from)?;
-    let projects = identities::any::list(&storage)
-        .map_err(Error::from)?
-        .filter_map(|res| {
-            res.map(|id| match id {
-                SomeIdentity::Project(project) => {
-                    use either::Either;
-
-                    if !project.delegations().iter().any(|d| match d {
-                        Either::Right(indirect) => indirect.urn() == delegate,
-                        Either::Left(_) => false,
-                    }) {
-                        return None;
-                    }
-
-                    let meta: project::Metadata = project.try_into().ok()?;
-                    let head = get_head_commit(&repo, &meta.urn, &meta.default_branch).ok()?;
-
-                    Some(Info {
-                        meta,
-                        head: head.id,
-                    })
-                }
-                _ => None,
+    let repo = git2::Repository::open_bare(&ctx.paths.git_dir()).map_err(Error::from)?;
+    let infos = ctx
+        .projects
+        .projects()
+        .into_iter()
+        .filter(|p| ctx.projects.can_read(&p.urn, reader.as_ref()))
+        .filter(|p| {
+            p.delegations.iter().any(|d| match d {
+                Delegation::Indirect { urn, .. } => *urn == delegate,
+                Delegation::Direct(_) => false,
             })
-            .transpose()
         })
+        .filter(|p| p.default_branch.is_some())
+        .map(|p| ctx.indexed_info(&repo, &storage, &p.urn))
         .collect::<Result<Vec<_>, _>>()
         .map_err(Error::from)?;
+    let projects = infos.iter().map(|info| info.as_ref()).collect::<Vec<_>>();
 
     Ok(warp::reply::json(&projects))
 }
 
-async fn browse<T, F>(reference: Reference<Single>, paths: Paths, callback: F) -> Result<T, Error>
+/// List project aliases, and the aliases that are ambiguous.
+async fn aliases_handler(ctx: Context, reader: Option<PeerId>) -> Result<impl Reply, Rejection> {
+    let aliases = ctx.projects.aliases();
+    let can_read = |urn: &Urn| ctx.projects.can_read(urn, reader.as_ref());
+
+    let visible = aliases
+        .aliases()
+        .iter()
+        .filter(|&(_, urn)| can_read(urn))
+        .collect::<BTreeMap<_, _>>();
+    let collisions = aliases
+        .collisions()
+        .iter()
+        .map(|(alias, urns)| {
+            (
+                alias,
+                urns.iter().filter(|&u| can_read(u)).collect::<Vec<_>>(),
+            )
+        })
+        .filter(|(_, urns)| !urns.is_empty())
+        .collect::<BTreeMap<_, _>>();
+
+    Ok(warp::reply::json(&json!({
+        "aliases": visible,
+        "collisions": collisions,
+    })))
+}
+
+/// List the projects a peer is a delegate of, directly or through a personal identity, or
+/// is tracked in.
+async fn peer_projects_handler(
+    ctx: Context,
+    reader: Option<PeerId>,
+    peer: PeerId,
+) -> Result<impl Reply, Rejection> {
+    let repo = git2::Repository::open_bare(&ctx.paths.git_dir()).map_err(Error::from)?;
+    let storage = ReadOnly::open(&ctx.paths).map_err(Error::from)?;
+
+    let mut projects = ctx
+        .projects
+        .by_peer(&peer)
+        .into_iter()
+        .filter(|(project, _)| project.default_branch.is_some())
+        .filter(|(project, _)| ctx.projects.can_read(&project.urn, reader.as_ref()))
+        .collect::<Vec<_>>();
+    projects.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+
+    let projects = projects
+        .into_iter()
+        .filter_map(|(project, relationships)| {
+            let info = ctx.indexed_info(&repo, &storage, &project.urn).ok()?;
+
+            Some(json!({
+                "project": project::Entry::new(&info, &project),
+                "relationships": relationships
+                    .into_iter()
+                    .map(project::Relationship::from)
+                    .collect::<Vec<_>>(),
+            }))
+        })
+        .collect::<Vec<_>>();
+
+    Ok(warp::reply::json(&projects))
+}
+
+async fn browse<T, F>(
+    project: Urn,
+    commit: git2::Oid,
+    paths: Paths,
+    callback: F,
+) -> Result<T, Error>
 where
     F: FnOnce(&mut git::Browser) -> Result<T, radicle_source::Error> + Send,
 {
-    let namespace = git::namespace::Namespace::try_from(
-        reference
-            .namespace
-            .ok_or(Error::MissingNamespace)?
-            .to_string()
-            .as_str(),
-    )
-    .map_err(Error::from)?;
-
-    let revision: git::Rev = match git::Oid::from_str(reference.name.as_str()) {
-        Ok(oid) => oid.try_into().map_err(|_| Error::NotFound)?,
-        Err(_) => remote_branch(
-            &reference.name.to_string(),
-            &reference.remote.ok_or(Error::NotFound)?,
-        )
-        .try_into()
-        .map_err(|_| Error::NotFound)?,
-    };
+    let namespace =
+        git::namespace::Namespace::try_from(project.encode_id().as_str()).map_err(Error::from)?;
+    let revision: git::Rev = commit.try_into().map_err(|_| Error::NotFound)?;
     let repo = git::Repository::new(paths.git_dir())?;
     let mut browser = git::Browser::new_with_namespace(&repo, &namespace, revision)?;
 
     Ok(callback(&mut browser)?)
 }
 
-fn project_info(urn: Urn, paths: Paths) -> Result<Info, Error> {
-    let repo = git::Repository::new(paths.git_dir())?;
-    let storage = ReadOnly::open(&paths)?;
+fn project_info(urn: Urn, ctx: &Context) -> Result<Info, Error> {
+    let repo = git2::Repository::open_bare(ctx.paths.git_dir())?;
+    let storage = ReadOnly::open(&ctx.paths)?;
     let project = identities::project::get(&storage, &urn)?.ok_or(Error::NotFound)?;
     let meta: project::Metadata = project.try_into()?;
-    let head = get_head_commit(&repo, &urn, &meta.default_branch)?;
 
-    Ok(Info {
-        head: head.id,
+    Ok(get_info(&repo, meta, ctx.quorum))
+}
+
+/// Get the indexed projects that can be served to a reader. Projects without a default
+/// branch are left out.
+fn get_projects(ctx: &Context, reader: Option<&PeerId>) -> Vec<project_index::Project> {
+    ctx.projects
+        .projects()
+        .into_iter()
+        .filter(|p| p.default_branch.is_some())
+        .filter(|p| ctx.projects.can_read(&p.urn, reader))
+        .collect()
+}
+
+/// Get the info of a project. Projects without local state are included, with no head.
+fn get_info(repo: &git2::Repository, meta: project::Metadata, quorum: Option<usize>) -> Info {
+    let head = get_head_commit(repo, &meta.urn, &meta.default_branch).ok();
+    let heads = get_delegate_heads(repo, &meta);
+    let canonical =
+        Canonical::compute(repo, &meta.delegates, &heads, quorum).unwrap_or_else(|err| {
+            tracing::warn!("Failed to compute canonical head for {}: {}", meta.urn, err);
+            None
+        });
+
+    Info {
+        heads: heads
+            .into_iter()
+            .map(|(peer, oid)| (peer, oid.to_string()))
+            .collect(),
         meta,
-    })
+        head,
+        canonical,
+    }
 }
 
 fn get_head_commit(
-    repo: &git::Repository,
+    repo: &git2::Repository,
     urn: &Urn,
     default_branch: &str,
-) -> Result<git::Commit, Error> {
-    let namespace = git::namespace::Namespace::try_from(urn.encode_id().as_str())?;
-    let browser =
-        git::Browser::new_with_namespace(repo, &namespace, git::Branch::local(default_branch))
-            .map_err(|_| Error::MissingLocalState)?;
-    let history = browser.get();
-
-    Ok(history.first().to_owned())
-}
-
-fn remote_branch(branch_name: &str, peer_id: &PeerId) -> git::Branch {
-    // NOTE<sebastinez>: We should be able to pass simply a branch name without heads/ and be able to query that later.
-    // Needs work on radicle_surf I assume.
-    git::Branch::remote(
-        &format!("heads/{}", branch_name),
-        &peer_id.default_encoding(),
+) -> Result<git2::Oid, Error> {
+    let head = format!(
+        "refs/namespaces/{}/refs/heads/{}",
+        urn.encode_id(),
+        default_branch
+    );
+
+    repo.find_reference(&head)
+        .and_then(|r| r.peel_to_commit())
+        .map(|c| c.id())
+        .map_err(|_| Error::MissingLocalState)
+}
+
+/// Get the default branch head of each delegate, from `refs/remotes/<peer>/heads/<default>`.
+/// Delegates we don't have refs for are left out.
+fn get_delegate_heads(
+    repo: &git2::Repository,
+    meta: &project::Metadata,
+) -> HashMap<PeerId, git2::Oid> {
+    meta.delegates
+        .iter()
+        .flat_map(|d| d.ids())
+        .filter_map(|peer| {
+            let head = format!(
+                "refs/namespaces/{}/refs/remotes/{}/heads/{}",
+                meta.urn.encode_id(),
+                peer.default_encoding(),
+                meta.default_branch
+            );
+            let oid = repo
+                .find_reference(&head)
+                .and_then(|r| r.peel_to_commit())
+                .ok()?
+                .id();
+
+            Some((peer, oid))
+        })
+        .collect()
+}
+
+/// Get the name of the most recent tag of a project, by commit time. Local tags are
+/// preferred over the delegates' tags.
+fn get_latest_tag(repo: &git2::Repository, urn: &Urn) -> Option<String> {
+    let namespace = format!("refs/namespaces/{}/refs/", urn.encode_id());
+
+    for glob in &["tags/*", "remotes/*/tags/*"] {
+        let latest = repo
+            .references_glob(&format!("{}{}", namespace, glob))
+            .ok()?
+            .filter_map(|r| r.ok())
+            .filter_map(|r| {
+                let name = r.name()?.rsplit_once("/tags/")?.1.to_owned();
+                let time = r.peel_to_commit().ok()?.time().seconds();
+
+                Some((time, name))
+            })
+            .max();
+
+        if let Some((_, name)) = latest {
+            return Some(name);
+        }
+    }
+    None
+}
+
+/// Get the peers tracked in a project.
+fn get_tracked_peers(ctx: &Context, urn: &Urn) -> Result<HashSet<PeerId>, Error> {
+    let storage = ReadOnly::open(&ctx.paths)?;
+    let tracked = tracking::tracked(&storage, Some(urn))
+        .map_err(|_| Error::NotFound)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(tracked.into_iter().filter_map(|t| t.peer_id()).collect())
+}
+
+/// Get the identities commit signatures are matched against: the delegates of a project,
+/// and the peers it tracks.
+fn get_signers(ctx: &Context, urn: &Urn) -> Result<Vec<signature::Signer>, Error> {
+    let storage = ReadOnly::open(&ctx.paths)?;
+    let project = identities::project::get(&storage, urn)?.ok_or(Error::NotFound)?;
+    let meta: project::Metadata = project.try_into()?;
+    let delegates = meta
+        .delegates
+        .iter()
+        .flat_map(|d| d.ids())
+        .collect::<HashSet<_>>();
+    let tracked = get_tracked_peers(ctx, urn)?;
+
+    Ok(delegates
+        .iter()
+        .map(|peer| (*peer, true))
+        .chain(
+            tracked
+                .into_iter()
+                .filter(|p| !delegates.contains(p))
+                .map(|peer| (peer, false)),
+        )
+        .map(|(peer, delegate)| signature::Signer {
+            peer,
+            name: get_person_name(&storage, urn, peer),
+            delegate,
+        })
+        .collect())
+}
+
+/// Base URL of the API, as reached by the client.
+fn base_url(ctx: &Context, host: Option<String>) -> String {
+    let scheme = if ctx.tls { "https" } else { "http" };
+
+    format!(
+        "{}://{}",
+        scheme,
+        host.unwrap_or_else(|| String::from("localhost"))
     )
 }
+
+/// Build a feed entry for a commit, linking to the commit JSON and, if configured, the
+/// web client.
+fn feed_entry(
+    ctx: &Context,
+    base: &str,
+    urn: &Urn,
+    commit: &git2::Commit,
+    title: String,
+) -> feed::Entry {
+    let author = commit.author();
+    let url = format!("{}/v1/projects/{}/commits/{}", base, urn, commit.id());
+    let mut links = Vec::new();
+
+    if let Some(web) = &ctx.web_url {
+        links.push((
+            "text/html",
+            format!(
+                "{}/{}/commits/{}",
+                web.trim_end_matches('/'),
+                urn,
+                commit.id()
+            ),
+        ));
+    }
+    links.push(("application/json", url.clone()));
+
+    feed::Entry {
+        id: url,
+        title,
+        updated: commit.time().seconds(),
+        author: author.name().unwrap_or_default().to_owned(),
+        email: author.email().unwrap_or_default().to_owned(),
+        summary: commit.message().unwrap_or_default().trim().to_owned(),
+        links,
+    }
+}
+
+/// Reply with an Atom feed.
+fn atom(feed: feed::Feed) -> impl Reply {
+    warp::reply::with_header(feed.render(), "Content-Type", "application/atom+xml")
+}
+
+/// Get a peer's personal identity, as seen in a project.
+fn get_person(storage: &ReadOnly, urn: &Urn, peer: PeerId) -> Option<identities::Person> {
+    let person_urn = Urn::try_from(Reference::rad_self(
+        Namespace::from(urn.clone()),
+        Some(peer),
+    ))
+    .ok()?;
+
+    identities::person::get(storage, &person_urn).ok()?
+}
+
+/// Get the name of a peer's personal identity, as seen in a project.
+fn get_person_name(storage: &ReadOnly, urn: &Urn, peer: PeerId) -> Option<String> {
+    get_person(storage, urn, peer).map(|person| person.subject().name.to_string())
+}
+
+/// Find a blob by path, in the tree of a commit.
+fn find_blob(
+    repo: &git2::Repository,
+    commit: git2::Oid,
+    path: &str,
+) -> Result<git2::Blob<'_>, Error> {
+    let tree = repo
+        .find_commit(commit)
+        .map_err(|_| Error::NotFound)?
+        .tree()?;
+    let entry = tree
+        .get_path(std::path::Path::new(path))
+        .map_err(|_| Error::NotFound)?;
+
+    repo.find_blob(entry.id()).map_err(|_| Error::NotFound)
+}
+
+/// Truncate text content to at most `limit` bytes, at a line break if possible.
+fn truncate(content: &[u8], limit: usize) -> String {
+    let content = &content[..limit.min(content.len())];
+    let end = content
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map(|i| i + 1)
+        .unwrap_or(content.len());
+
+    String::from_utf8_lossy(&content[..end]).into_owned()
+}
+
+/// Resolve a revision to a commit in the project, optionally within a peer's refs.
+fn resolve(
+    paths: &Paths,
+    project: &Urn,
+    revision: &Revision,
+    peer: Option<&PeerId>,
+) -> Result<git2::Oid, Error> {
+    let repo = git2::Repository::open_bare(paths.git_dir())?;
+
+    revision.resolve(&repo, project, peer)
+}
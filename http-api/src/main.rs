@@ -1,5 +1,5 @@
-use std::net;
 use std::path::PathBuf;
+use std::{net, process};
 
 use radicle_http_api as api;
 
@@ -30,6 +30,36 @@ pub struct Options {
     #[argh(option, default = r#"String::from("base16-ocean.dark")"#)]
     pub theme: String,
 
+    /// size above which text blobs are truncated, in bytes (default: 1 MiB)
+    #[argh(option, default = "1024 * 1024")]
+    pub blob_limit: usize,
+
+    /// maximum size of image previews embedded in blob responses, in bytes (default: 256 KiB)
+    #[argh(option, default = "256 * 1024")]
+    pub preview_limit: usize,
+
+    /// number of delegates that must agree on a project's canonical head (default: majority)
+    #[argh(option)]
+    pub quorum: Option<usize>,
+
+    /// base URL of the web client, used for links in feeds
+    #[argh(option)]
+    pub web_url: Option<String>,
+
+    /// notes ref to read commit notes from, eg. refs/notes/ci; may be repeated
+    /// (default: refs/notes/commits)
+    #[argh(option)]
+    pub notes_ref: Vec<String>,
+
+    /// file of project aliases, one "<alias> <urn>" per line, taking precedence over names
+    #[argh(option)]
+    pub alias_file: Option<PathBuf>,
+
+    /// file of rules deciding which projects are served and to whom, eg. "deny delegate
+    /// <peer-id>" or "read <urn> <peer-id>"; denied projects are treated as not found
+    #[argh(option)]
+    pub policy_file: Option<PathBuf>,
+
     /// either "plain" or "gcp" (gcp available only when compiled-in)
     #[argh(option, default = "LogFmt::Plain")]
     pub log_format: LogFmt,
@@ -49,6 +79,13 @@ impl From<Options> for api::Options {
             tls_key: other.tls_key,
             listen: other.listen,
             theme: other.theme,
+            blob_limit: other.blob_limit,
+            preview_limit: other.preview_limit,
+            quorum: other.quorum,
+            web_url: other.web_url,
+            notes_refs: other.notes_ref,
+            alias_file: other.alias_file,
+            policy_file: other.policy_file,
         }
     }
 }
@@ -60,5 +97,11 @@ async fn main() {
     shared::init_logger(options.log_format);
     tracing::info!("version {}-{}", env!("CARGO_PKG_VERSION"), env!("GIT_HEAD"));
 
-    api::run(options.into()).await;
+    match api::run(options.into()).await {
+        Ok(()) => {}
+        Err(err) => {
+            tracing::error!("Fatal: {}", err);
+            process::exit(1);
+        }
+    }
 }
@@ -0,0 +1,66 @@
+//! Git notes attached to commits.
+use radicle_daemon::{PeerId, Urn};
+use serde::Serialize;
+
+/// Notes ref read when none are configured.
+pub const DEFAULT_NOTES_REF: &str = "refs/notes/commits";
+
+/// A note on a commit.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Note {
+    /// Notes ref the note was found in, eg. `refs/notes/ci`, relative to the namespace.
+    #[serde(rename = "ref")]
+    pub refname: String,
+    /// Peer whose notes these are, or `None` for the project's own notes.
+    pub peer: Option<PeerId>,
+    pub message: String,
+}
+
+/// Get the notes on a commit from the given notes refs, in the project's namespace and, if
+/// given, in a peer's namespace.
+///
+/// Notes refs are given as `refs/notes/<name>`. A peer's notes are read from
+/// `refs/remotes/<peer>/notes/<name>`.
+pub fn notes(
+    repo: &git2::Repository,
+    urn: &Urn,
+    peer: Option<&PeerId>,
+    refs: &[String],
+    oid: git2::Oid,
+) -> Result<Vec<Note>, git2::Error> {
+    let namespace = format!("refs/namespaces/{}/", urn.encode_id());
+    let mut notes = Vec::new();
+
+    for refname in refs {
+        let name = match refname.strip_prefix("refs/notes/") {
+            Some(name) => name,
+            None => continue,
+        };
+        let mut sources = vec![(None, format!("{}{}", namespace, refname))];
+
+        if let Some(peer) = peer {
+            sources.push((
+                Some(*peer),
+                format!(
+                    "{}refs/remotes/{}/notes/{}",
+                    namespace,
+                    peer.default_encoding(),
+                    name
+                ),
+            ));
+        }
+        for (peer, notes_ref) in sources {
+            match repo.find_note(Some(&notes_ref), oid) {
+                Ok(note) => notes.push(Note {
+                    refname: refname.clone(),
+                    peer,
+                    message: note.message().unwrap_or_default().to_owned(),
+                }),
+                Err(e) if e.code() == git2::ErrorCode::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    Ok(notes)
+}
@@ -0,0 +1,180 @@
+//! Patches: contributor branches presented as proposed changes to the default branch.
+use radicle_daemon::PeerId;
+use serde::{Deserialize, Serialize};
+
+use crate::project::string;
+
+/// Patch status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// The patch head is not in the canonical history.
+    Open,
+    /// The patch head is reachable from the canonical head.
+    Merged,
+}
+
+/// A commit of a patch, or of a file's history.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Commit {
+    #[serde(with = "string")]
+    pub id: git2::Oid,
+    pub summary: String,
+    pub author: String,
+    pub email: String,
+    /// Committer time, in seconds since epoch.
+    pub timestamp: i64,
+}
+
+impl<'a> From<&git2::Commit<'a>> for Commit {
+    fn from(commit: &git2::Commit<'a>) -> Self {
+        let author = commit.author();
+
+        Self {
+            id: commit.id(),
+            summary: commit.summary().unwrap_or_default().to_owned(),
+            author: author.name().unwrap_or_default().to_owned(),
+            email: author.email().unwrap_or_default().to_owned(),
+            timestamp: commit.time().seconds(),
+        }
+    }
+}
+
+/// Diff statistics of a patch, against its merge base.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    pub files: usize,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+/// A patch, ie. a peer's branch compared to the canonical head.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Patch {
+    /// Peer proposing the patch.
+    pub peer: PeerId,
+    /// Branch name.
+    pub branch: String,
+    /// Branch head.
+    #[serde(with = "string")]
+    pub head: git2::Oid,
+    /// Merge base with the canonical head.
+    #[serde(with = "string")]
+    pub merge_base: git2::Oid,
+    /// Title, taken from the summary of the first commit.
+    pub title: String,
+    /// Description, taken from the body of the first commit.
+    pub description: String,
+    pub status: Status,
+    /// Commits of the patch, newest first.
+    pub commits: Vec<Commit>,
+    pub stats: Stats,
+    /// Time of the latest commit of the patch, or for merged patches, of the commit that
+    /// merged it.
+    pub timestamp: i64,
+}
+
+impl Patch {
+    /// Load a patch given a branch head and the canonical head it proposes changes to.
+    /// Returns `None` if the branch shares no history with the canonical head.
+    pub fn load(
+        repo: &git2::Repository,
+        peer: PeerId,
+        branch: String,
+        head: git2::Oid,
+        base: git2::Oid,
+    ) -> Result<Option<Self>, git2::Error> {
+        let merge_base = match repo.merge_base(head, base) {
+            Ok(oid) => oid,
+            Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let status = if head == base || repo.graph_descendant_of(base, head)? {
+            Status::Merged
+        } else {
+            Status::Open
+        };
+
+        let mut walk = repo.revwalk()?;
+        walk.push(head)?;
+        walk.hide(merge_base)?;
+
+        let mut commits = Vec::new();
+        for oid in walk {
+            commits.push(Commit::from(&repo.find_commit(oid?)?));
+        }
+
+        // The first commit of the patch is the oldest. For merged patches, there may be no
+        // commits left on top of the merge base, in which case we use the head.
+        let first = match commits.last() {
+            Some(commit) => repo.find_commit(commit.id)?,
+            None => repo.find_commit(head)?,
+        };
+        let message = first.message().unwrap_or_default();
+        let (title, description) = match message.split_once('\n') {
+            Some((title, description)) => (title.trim(), description.trim()),
+            None => (message.trim(), ""),
+        };
+
+        let diff = repo.diff_tree_to_tree(
+            Some(&repo.find_commit(merge_base)?.tree()?),
+            Some(&repo.find_commit(head)?.tree()?),
+            None,
+        )?;
+        let stats = diff.stats()?;
+        let timestamp = match status {
+            Status::Open => commits
+                .iter()
+                .map(|c| c.timestamp)
+                .max()
+                .unwrap_or_default(),
+            Status::Merged => merged_at(repo, head, base)?,
+        };
+
+        Ok(Some(Self {
+            peer,
+            branch,
+            head,
+            merge_base,
+            title: title.to_owned(),
+            description: description.to_owned(),
+            status,
+            commits,
+            stats: Stats {
+                files: stats.files_changed(),
+                additions: stats.insertions(),
+                deletions: stats.deletions(),
+            },
+            timestamp,
+        }))
+    }
+}
+
+/// Time of the commit that merged a patch head into the canonical history. Fast-forwarded
+/// heads have no such commit, so the time of the head is used.
+fn merged_at(
+    repo: &git2::Repository,
+    head: git2::Oid,
+    base: git2::Oid,
+) -> Result<i64, git2::Error> {
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+    walk.push(base)?;
+    walk.hide(head)?;
+
+    for oid in walk {
+        let commit = repo.find_commit(oid?)?;
+        let mut parents = commit.parent_ids();
+
+        if parents.next() == Some(head) {
+            break;
+        }
+        if parents.any(|p| p == head) {
+            return Ok(commit.time().seconds());
+        }
+    }
+    Ok(repo.find_commit(head)?.time().seconds())
+}
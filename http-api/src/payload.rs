@@ -0,0 +1,127 @@
+//! Identity payload extensions.
+//!
+//! Besides their subject, identity payloads can carry extensions, keyed by a namespace URL.
+//! Known extensions, ie. the ones defined by radicle-link and upstream, are decoded. Others
+//! are passed through as raw JSON.
+use serde::{Deserialize, Serialize};
+
+/// Namespace of the project payload subject.
+pub const PROJECT_NAMESPACE: &str = "https://radicle.xyz/link/identities/project/v1";
+/// Namespace of the person payload subject.
+pub const PERSON_NAMESPACE: &str = "https://radicle.xyz/link/identities/person/v1";
+/// Namespace of the Ethereum address claim, as set by upstream on person identities.
+pub const ETHEREUM_CLAIM_NAMESPACE: &str = "https://radicle.xyz/ethereum/claim/v1";
+
+/// A payload extension.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Extension {
+    /// Namespace URL of the extension.
+    pub namespace: String,
+    #[serde(flatten)]
+    pub value: Value,
+}
+
+/// Value of a payload extension.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum Value {
+    /// Ethereum address claim. The expiration is an RFC 3339 date.
+    #[serde(rename_all = "camelCase")]
+    Ethereum {
+        address: String,
+        expiration: Option<String>,
+    },
+    /// Extension of an unknown type, or that didn't decode.
+    Other { value: serde_json::Value },
+}
+
+/// Get the extensions of an identity payload, given the namespace of its subject.
+pub fn extensions<P: Serialize>(payload: &P, subject: &str) -> Vec<Extension> {
+    let fields = match serde_json::to_value(payload) {
+        Ok(serde_json::Value::Object(fields)) => fields,
+        _ => return Vec::new(),
+    };
+
+    fields
+        .into_iter()
+        .filter(|(namespace, _)| namespace != subject)
+        .map(|(namespace, value)| Extension {
+            value: decode(&namespace, value),
+            namespace,
+        })
+        .collect()
+}
+
+/// Decode a known extension, falling back to the raw value.
+fn decode(namespace: &str, value: serde_json::Value) -> Value {
+    #[derive(Deserialize)]
+    struct Ethereum {
+        address: String,
+        expiration: Option<String>,
+    }
+
+    let decoded = match namespace {
+        ETHEREUM_CLAIM_NAMESPACE => serde_json::from_value::<Ethereum>(value.clone())
+            .map(|e| Value::Ethereum {
+                address: e.address,
+                expiration: e.expiration,
+            })
+            .ok(),
+        _ => None,
+    };
+    decoded.unwrap_or(Value::Other { value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Payload of a person identity with an Ethereum claim and an unknown extension. It is
+    /// written by hand in the format upstream stores, with made up values.
+    const PERSON_PAYLOAD: &str = r#"{
+        "https://radicle.xyz/link/identities/person/v1": {
+            "name": "alice"
+        },
+        "https://radicle.xyz/ethereum/claim/v1": {
+            "address": "0x9a4ff5b2a6a3c3ab8b1a8c2fe3d1d5d7b0c1a2e3",
+            "expiration": "2022-03-01T12:00:00Z"
+        },
+        "https://example.com/avatar/v1": {
+            "url": "https://example.com/alice.png"
+        }
+    }"#;
+
+    #[test]
+    fn test_person_extensions() {
+        let payload = serde_json::from_str::<serde_json::Value>(PERSON_PAYLOAD).unwrap();
+        let mut extensions = extensions(&payload, PERSON_NAMESPACE);
+        extensions.sort_by(|a, b| a.namespace.cmp(&b.namespace));
+
+        assert_eq!(extensions.len(), 2);
+        assert_eq!(extensions[0].namespace, "https://example.com/avatar/v1");
+        assert!(matches!(
+            &extensions[0].value,
+            Value::Other { value } if value["url"] == "https://example.com/alice.png"
+        ));
+        assert_eq!(extensions[1].namespace, ETHEREUM_CLAIM_NAMESPACE);
+        assert!(matches!(
+            &extensions[1].value,
+            Value::Ethereum { address, expiration }
+                if address == "0x9a4ff5b2a6a3c3ab8b1a8c2fe3d1d5d7b0c1a2e3"
+                    && expiration.as_deref() == Some("2022-03-01T12:00:00Z")
+        ));
+    }
+
+    #[test]
+    fn test_malformed_extension() {
+        let payload = serde_json::json!({
+            PROJECT_NAMESPACE: { "name": "radicle-link" },
+            ETHEREUM_CLAIM_NAMESPACE: { "addr": "0x0" },
+        });
+        let extensions = extensions(&payload, PROJECT_NAMESPACE);
+
+        assert_eq!(extensions.len(), 1);
+        assert!(matches!(extensions[0].value, Value::Other { .. }));
+    }
+}
@@ -1,11 +1,13 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 
 use either::Either;
 use radicle_daemon::{PeerId, Urn};
 use serde::{Deserialize, Serialize};
 
+use crate::canonical::Canonical;
 use crate::error;
+use crate::payload;
 
 /// Project info.
 #[derive(Serialize)]
@@ -14,9 +16,83 @@ pub struct Info {
     /// Project metadata.
     #[serde(flatten)]
     pub meta: Metadata,
-    /// Project HEAD commit.
-    #[serde(with = "string")]
-    pub head: git2::Oid,
+    /// Project HEAD commit. This is `None` if the project has no local state yet, ie.
+    /// the seed has no local default branch.
+    #[serde(with = "string::option")]
+    pub head: Option<git2::Oid>,
+    /// Default branch head of each delegate, as found in their remote refs.
+    pub heads: HashMap<PeerId, String>,
+    /// Canonical head, agreed upon by the delegates.
+    pub canonical: Option<Canonical>,
+}
+
+impl Info {
+    /// The canonical head, or the local head if the delegates don't agree on one.
+    pub fn canonical_head(&self) -> Option<git2::Oid> {
+        self.canonical.as_ref().map(|c| c.head).or(self.head)
+    }
+}
+
+/// A listed project, with statistics from the project index.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry<'a> {
+    #[serde(flatten)]
+    pub info: &'a Info,
+    /// Time of the first identity revision, in seconds since epoch.
+    pub created: i64,
+    /// Time of the most recent commit in the namespace, in seconds since epoch.
+    pub updated: i64,
+    pub counts: Counts,
+}
+
+impl<'a> Entry<'a> {
+    pub fn new(info: &'a Info, project: &project_index::Project) -> Self {
+        Self {
+            info,
+            created: project.created,
+            updated: project.updated,
+            counts: Counts {
+                commits: project.commits,
+                branches: project.branches,
+                tracked_peers: project.tracked.len(),
+            },
+        }
+    }
+}
+
+/// Cheap per-project counts.
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Counts {
+    /// Commits reachable from the project head.
+    pub commits: usize,
+    /// Local branches.
+    pub branches: usize,
+    /// Tracked peers.
+    pub tracked_peers: usize,
+}
+
+/// How a peer relates to a project.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum Relationship {
+    /// The peer is a delegate of the project.
+    Delegate,
+    /// The peer acts on behalf of a personal identity that is a delegate of the project.
+    IndirectDelegate { urn: Urn },
+    /// The project tracks the peer.
+    Tracked,
+}
+
+impl From<project_index::Relationship> for Relationship {
+    fn from(relationship: project_index::Relationship) -> Self {
+        match relationship {
+            project_index::Relationship::Delegate => Self::Delegate,
+            project_index::Relationship::IndirectDelegate(urn) => Self::IndirectDelegate { urn },
+            project_index::Relationship::Tracked => Self::Tracked,
+        }
+    }
 }
 
 /// Project delegate.
@@ -36,10 +112,18 @@ impl Delegate {
             Self::Indirect { ids, .. } => ids.contains(other),
         }
     }
+
+    /// Peers acting on behalf of this delegate.
+    pub fn ids(&self) -> Vec<PeerId> {
+        match self {
+            Self::Direct { id } => vec![*id],
+            Self::Indirect { ids, .. } => ids.iter().cloned().collect(),
+        }
+    }
 }
 
 /// Project metadata.
-#[derive(Deserialize, Serialize)]
+#[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Metadata {
     /// Project urn.
@@ -52,6 +136,8 @@ pub struct Metadata {
     pub default_branch: String,
     /// List of delegates.
     pub delegates: Vec<Delegate>,
+    /// Payload extensions of the project identity.
+    pub extensions: Vec<payload::Extension>,
 }
 
 impl TryFrom<radicle_daemon::Project> for Metadata {
@@ -95,14 +181,16 @@ impl TryFrom<radicle_daemon::Project> for Metadata {
                 .map_or_else(|| "".into(), |desc| desc.to_string()),
             default_branch,
             delegates,
+            extensions: payload::extensions(project.payload(), payload::PROJECT_NAMESPACE),
         })
     }
 }
 
-mod string {
+pub(crate) mod string {
     use std::fmt::Display;
+    use std::str::FromStr;
 
-    use serde::Serializer;
+    use serde::{de, Deserialize, Deserializer, Serializer};
 
     pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -111,4 +199,44 @@ mod string {
     {
         serializer.collect_str(value)
     }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+
+    pub mod option {
+        use std::fmt::Display;
+        use std::str::FromStr;
+
+        use serde::{de, Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            T: Display,
+            S: Serializer,
+        {
+            match value {
+                Some(value) => serializer.collect_str(value),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+        where
+            T: FromStr,
+            T::Err: Display,
+            D: Deserializer<'de>,
+        {
+            Option::<String>::deserialize(deserializer)?
+                .map(|s| s.parse().map_err(de::Error::custom))
+                .transpose()
+        }
+    }
 }
@@ -0,0 +1,633 @@
+//! README discovery and rendering.
+use std::path::Path;
+
+use radicle_daemon::Urn;
+use serde::Serialize;
+
+use crate::error::Error;
+
+/// README file names we look for, in order of preference. Matching is case-insensitive.
+pub const CANDIDATES: &[&str] = &[
+    "readme.md",
+    "readme.markdown",
+    "readme.mdown",
+    "readme.rst",
+    "readme.txt",
+    "readme",
+];
+
+/// README markup format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// CommonMark, with GFM tables and task lists.
+    Markdown,
+    /// reStructuredText.
+    Rst,
+    /// Plain text, not rendered.
+    Plain,
+}
+
+impl Format {
+    /// Guess the format of a README from its file name.
+    pub fn from_name(name: &str) -> Self {
+        let name = name.to_lowercase();
+
+        match name.rsplit_once('.').map(|(_, ext)| ext) {
+            Some("md" | "markdown" | "mdown") => Self::Markdown,
+            Some("rst") => Self::Rst,
+            _ => Self::Plain,
+        }
+    }
+}
+
+/// A README found in a tree.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Readme {
+    /// Path of the README, relative to the repository root.
+    pub path: String,
+    /// Raw README content.
+    pub content: String,
+    /// Markup format of the content.
+    pub format: Format,
+    /// Sanitized HTML rendering, if the format supports it.
+    pub html: Option<String>,
+}
+
+impl Readme {
+    /// Find and render the README in the directory `dir` of the given commit.
+    pub fn find(
+        repo: &git2::Repository,
+        urn: &Urn,
+        commit: git2::Oid,
+        dir: &str,
+    ) -> Result<Option<Self>, Error> {
+        let dir = dir.trim_matches('/');
+        let root = repo
+            .find_commit(commit)
+            .map_err(|_| Error::NotFound)?
+            .tree()?;
+        let tree = if dir.is_empty() {
+            root.clone()
+        } else {
+            root.get_path(Path::new(dir))
+                .map_err(|_| Error::NotFound)?
+                .to_object(repo)?
+                .peel_to_tree()
+                .map_err(|_| Error::NotFound)?
+        };
+
+        let found = CANDIDATES.iter().find_map(|candidate| {
+            tree.iter().find(|entry| {
+                entry.kind() == Some(git2::ObjectType::Blob)
+                    && entry
+                        .name()
+                        .is_some_and(|name| name.eq_ignore_ascii_case(candidate))
+            })
+        });
+        let entry = match found {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        let name = entry.name().unwrap_or_default().to_owned();
+        let blob = repo.find_blob(entry.id())?;
+        let content = String::from_utf8_lossy(blob.content()).into_owned();
+        let format = Format::from_name(&name);
+        let is_tree = |path: &str| {
+            root.get_path(Path::new(path))
+                .is_ok_and(|e| e.kind() == Some(git2::ObjectType::Tree))
+        };
+        let links = Links {
+            urn,
+            commit,
+            dir,
+            is_tree: &is_tree,
+        };
+        let html = match format {
+            Format::Markdown => Some(sanitize(&markdown(&content, &links))),
+            Format::Rst => Some(sanitize(&rst(&content, &links))),
+            Format::Plain => None,
+        };
+        let path = if dir.is_empty() {
+            name
+        } else {
+            format!("{}/{}", dir, name)
+        };
+
+        Ok(Some(Self {
+            path,
+            content,
+            format,
+            html,
+        }))
+    }
+}
+
+/// Rewrites relative links found in a README to API endpoints at the same commit.
+struct Links<'a> {
+    urn: &'a Urn,
+    commit: git2::Oid,
+    /// Directory of the README, relative links are resolved from here.
+    dir: &'a str,
+    /// Whether a path, relative to the repository root, is a directory.
+    is_tree: &'a dyn Fn(&str) -> bool,
+}
+
+impl<'a> Links<'a> {
+    /// Rewrite a link destination. Images always point to the raw blob endpoint, other links
+    /// point to the tree endpoint for directories and to the raw blob endpoint otherwise.
+    fn rewrite(&self, url: &str, image: bool) -> String {
+        if url.is_empty() || url.starts_with('#') || url.starts_with("//") || has_scheme(url) {
+            return url.to_owned();
+        }
+        let (target, fragment) = match url.find('#') {
+            Some(ix) => (&url[..ix], &url[ix..]),
+            None => (url, ""),
+        };
+        let target = target.split('?').next().unwrap_or_default();
+        let path = match target.strip_prefix('/') {
+            Some(absolute) => normalize("", absolute),
+            None => normalize(self.dir, target),
+        };
+        let path = match path {
+            Some(path) => path,
+            // The link escapes the repository root, leave it alone.
+            None => return url.to_owned(),
+        };
+        let endpoint = if !image && self.is_tree(&path) {
+            "tree"
+        } else {
+            "raw"
+        };
+
+        format!(
+            "/v1/projects/{}/{}/{}/{}{}",
+            self.urn, endpoint, self.commit, path, fragment
+        )
+    }
+
+    fn is_tree(&self, path: &str) -> bool {
+        path.is_empty() || (self.is_tree)(path)
+    }
+}
+
+/// Whether a URL starts with a scheme, eg. `https:` or `mailto:`.
+fn has_scheme(url: &str) -> bool {
+    match url.find(':') {
+        Some(ix) => {
+            ix > 0
+                && url[..ix]
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        }
+        None => false,
+    }
+}
+
+/// Join `path` onto `dir`, resolving `.` and `..` components.
+/// Returns `None` if the result would be outside of the root.
+fn normalize(dir: &str, path: &str) -> Option<String> {
+    let mut components: Vec<&str> = Vec::new();
+
+    for component in dir.split('/').chain(path.split('/')) {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop()?;
+            }
+            other => components.push(other),
+        }
+    }
+    Some(components.join("/"))
+}
+
+/// Sanitize rendered HTML. Besides the defaults, we allow the disabled checkboxes
+/// emitted for task lists.
+fn sanitize(html: &str) -> String {
+    ammonia::Builder::default()
+        .add_tags(&["input"])
+        .add_tag_attributes("input", &["type", "checked", "disabled"])
+        .clean(html)
+        .to_string()
+}
+
+/// Render CommonMark with GFM tables and task lists to HTML.
+fn markdown(src: &str, links: &Links) -> String {
+    use pulldown_cmark::{html, Event, Options, Parser, Tag};
+
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_FOOTNOTES);
+
+    let parser = Parser::new_ext(src, options).map(|event| match event {
+        Event::Start(Tag::Link(kind, dest, title)) => {
+            Event::Start(Tag::Link(kind, links.rewrite(&dest, false).into(), title))
+        }
+        Event::Start(Tag::Image(kind, dest, title)) => {
+            Event::Start(Tag::Image(kind, links.rewrite(&dest, true).into(), title))
+        }
+        other => other,
+    });
+    let mut output = String::new();
+    html::push_html(&mut output, parser);
+
+    output
+}
+
+/// Render reStructuredText to HTML.
+///
+/// Only a commonly used subset is supported: section titles, paragraphs, bullet and
+/// enumerated lists, literal blocks, `code-block` and `image` directives, and inline
+/// literals, emphasis and hyperlink references. Other directives and comments are dropped.
+fn rst(src: &str, links: &Links) -> String {
+    let lines: Vec<&str> = src.lines().collect();
+    let mut html = String::new();
+    // Title adornment characters, in order of appearance. This gives us the section levels.
+    let mut levels: Vec<char> = Vec::new();
+    let mut heading = |html: &mut String, adornment: char, title: &str| {
+        let level = match levels.iter().position(|c| *c == adornment) {
+            Some(ix) => ix + 1,
+            None => {
+                levels.push(adornment);
+                levels.len()
+            }
+        }
+        .min(6);
+        html.push_str(&format!(
+            "<h{}>{}</h{}>\n",
+            level,
+            rst_inline(title, links),
+            level
+        ));
+    };
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+
+        if line.trim().is_empty() {
+            i += 1;
+            continue;
+        }
+        // Title with overline and underline, or a transition.
+        if let Some(c) = adornment(line) {
+            if line.trim_end().len() >= 4 && lines.get(i + 1).is_none_or(|l| l.trim().is_empty()) {
+                html.push_str("<hr>\n");
+                i += 1;
+                continue;
+            }
+            if i + 2 < lines.len()
+                && !lines[i + 1].trim().is_empty()
+                && adornment(lines[i + 2]) == Some(c)
+            {
+                heading(&mut html, c, lines[i + 1].trim());
+                i += 3;
+                continue;
+            }
+        }
+        // Title with underline only.
+        if !line.starts_with(char::is_whitespace) && i + 1 < lines.len() {
+            if let Some(c) = adornment(lines[i + 1]) {
+                if lines[i + 1].trim_end().chars().count() >= line.trim_end().chars().count() {
+                    heading(&mut html, c, line.trim());
+                    i += 2;
+                    continue;
+                }
+            }
+        }
+        // Directives and comments.
+        if let Some(directive) = line.strip_prefix("..") {
+            let directive = directive.trim();
+            let (block, next) = rst_indented(&lines, i + 1);
+
+            if let Some(url) = directive
+                .strip_prefix("image::")
+                .or_else(|| directive.strip_prefix("figure::"))
+            {
+                html.push_str(&format!(
+                    "<p><img src=\"{}\"></p>\n",
+                    escape(&links.rewrite(url.trim(), true))
+                ));
+            } else if ["code-block::", "code::", "sourcecode::"]
+                .iter()
+                .any(|d| directive.starts_with(d))
+            {
+                // Skip directive options, eg. `:linenos:`.
+                let code = block
+                    .iter()
+                    .skip_while(|l| l.starts_with(':'))
+                    .skip_while(|l| l.is_empty())
+                    .cloned()
+                    .collect::<Vec<_>>();
+                rst_literal(&mut html, &code);
+            }
+            i = next;
+            continue;
+        }
+        // Lists.
+        if let Some((tag, _)) = rst_list_item(line) {
+            html.push_str(&format!("<{}>\n", tag));
+
+            while i < lines.len() {
+                let item = match rst_list_item(lines[i]) {
+                    Some((t, item)) if t == tag => item,
+                    _ => break,
+                };
+                let mut text = vec![item.trim()];
+                i += 1;
+
+                while i < lines.len()
+                    && lines[i].starts_with(char::is_whitespace)
+                    && !lines[i].trim().is_empty()
+                {
+                    text.push(lines[i].trim());
+                    i += 1;
+                }
+                while i < lines.len()
+                    && lines[i].trim().is_empty()
+                    && i + 1 < lines.len()
+                    && rst_list_item(lines[i + 1]).map(|(t, _)| t) == Some(tag)
+                {
+                    i += 1;
+                }
+                html.push_str(&format!(
+                    "<li>{}</li>\n",
+                    rst_inline(&text.join(" "), links)
+                ));
+            }
+            html.push_str(&format!("</{}>\n", tag));
+            continue;
+        }
+        // Paragraphs, optionally introducing a literal block with `::`.
+        let mut paragraph = Vec::new();
+        while i < lines.len() && !lines[i].trim().is_empty() {
+            if !paragraph.is_empty() && i + 1 < lines.len() && adornment(lines[i + 1]).is_some() {
+                break;
+            }
+            paragraph.push(lines[i].trim());
+            i += 1;
+        }
+        let mut text = paragraph.join(" ");
+        let literal = text.ends_with("::");
+
+        if literal {
+            text.truncate(text.len() - 1);
+            if text == ":" || text.ends_with(" :") {
+                text.truncate(text.len() - 1);
+            }
+        }
+        let text = text.trim_end();
+        if !text.is_empty() {
+            html.push_str(&format!("<p>{}</p>\n", rst_inline(text, links)));
+        }
+        if literal {
+            let (block, next) = rst_indented(&lines, i);
+            rst_literal(&mut html, &block);
+            i = next;
+        }
+    }
+    html
+}
+
+/// Returns the adornment character if the line is a section title adornment.
+fn adornment(line: &str) -> Option<char> {
+    let line = line.trim_end();
+    let first = line.chars().next()?;
+
+    if line.len() >= 2 && "=-`:'\"~^_*+#<>.".contains(first) && line.chars().all(|c| c == first) {
+        Some(first)
+    } else {
+        None
+    }
+}
+
+/// Returns the list tag and item text if the line starts a list item.
+fn rst_list_item(line: &str) -> Option<(&'static str, &str)> {
+    for bullet in &["- ", "* ", "+ "] {
+        if let Some(item) = line.strip_prefix(bullet) {
+            return Some(("ul", item));
+        }
+    }
+    let (marker, item) = line.split_once(". ")?;
+
+    if marker == "#" || (!marker.is_empty() && marker.chars().all(|c| c.is_ascii_digit())) {
+        Some(("ol", item))
+    } else {
+        None
+    }
+}
+
+/// Collect the indented block starting at `start`, dedented.
+/// Returns the block lines and the index of the first line after it.
+fn rst_indented(lines: &[&str], start: usize) -> (Vec<String>, usize) {
+    let mut end = start;
+
+    while end < lines.len()
+        && (lines[end].trim().is_empty() || lines[end].starts_with(char::is_whitespace))
+    {
+        end += 1;
+    }
+    let block = &lines[start..end];
+    let indent = block
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let mut block = block
+        .iter()
+        .map(|l| l.get(indent..).unwrap_or_default().trim_end().to_owned())
+        .skip_while(|l| l.is_empty())
+        .collect::<Vec<_>>();
+
+    while block.last().is_some_and(|l| l.is_empty()) {
+        block.pop();
+    }
+    (block, end)
+}
+
+fn rst_literal(html: &mut String, block: &[String]) {
+    if !block.is_empty() {
+        html.push_str(&format!(
+            "<pre><code>{}</code></pre>\n",
+            escape(&block.join("\n"))
+        ));
+    }
+}
+
+/// Render inline markup: ``literals``, **strong**, *emphasis*, `title references` and
+/// `hyperlink <references>`_.
+fn rst_inline(text: &str, links: &Links) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some(r) = rest.strip_prefix("``") {
+            if let Some(end) = r.find("``") {
+                out.push_str(&format!("<code>{}</code>", escape(&r[..end])));
+                rest = &r[end + 2..];
+                continue;
+            }
+        } else if let Some(r) = rest.strip_prefix("**") {
+            if let Some(end) = r.find("**") {
+                out.push_str(&format!("<strong>{}</strong>", escape(&r[..end])));
+                rest = &r[end + 2..];
+                continue;
+            }
+        } else if let Some(r) = rest.strip_prefix('*') {
+            if let Some(end) = r.find('*') {
+                out.push_str(&format!("<em>{}</em>", escape(&r[..end])));
+                rest = &r[end + 1..];
+                continue;
+            }
+        } else if let Some(r) = rest.strip_prefix('`') {
+            if let Some(end) = r.find('`') {
+                let inner = &r[..end];
+                let after = &r[end + 1..];
+
+                if let Some(after) = after.strip_prefix("__").or_else(|| after.strip_prefix('_')) {
+                    match inner.rfind('<') {
+                        Some(open) if inner.ends_with('>') => {
+                            let url = &inner[open + 1..inner.len() - 1];
+                            let label = inner[..open].trim();
+                            let label = if label.is_empty() { url } else { label };
+
+                            out.push_str(&format!(
+                                "<a href=\"{}\">{}</a>",
+                                escape(&links.rewrite(url, false)),
+                                escape(label)
+                            ));
+                        }
+                        _ => out.push_str(&escape(inner)),
+                    }
+                    rest = after;
+                } else {
+                    out.push_str(&format!("<cite>{}</cite>", escape(inner)));
+                    rest = after;
+                }
+                continue;
+            }
+        }
+        out.push_str(&escape(&rest[..c.len_utf8()]));
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URN: &str = "rad:git:hnrkbtw9t1of4ykjy6er4qqwxtc54k9943eto";
+    const COMMIT: &str = "f5d7e5a1a09a3c2a6f4e2b8f1d3c5e7a9b0c1d2e";
+
+    fn render(src: &str, dir: &str) -> String {
+        let urn = URN.parse::<Urn>().unwrap();
+        let is_tree = |path: &str| path == "docs";
+        let links = Links {
+            urn: &urn,
+            commit: git2::Oid::from_str(COMMIT).unwrap(),
+            dir,
+            is_tree: &is_tree,
+        };
+        rst(src, &links)
+    }
+
+    #[test]
+    fn test_rst_headings() {
+        let html = render("Title\n=====\n\nSection\n-------\n\nOther\n=====\n", "");
+
+        assert_eq!(html, "<h1>Title</h1>\n<h2>Section</h2>\n<h1>Other</h1>\n");
+    }
+
+    #[test]
+    fn test_rst_heading_with_overline() {
+        let html = render("=====\nTitle\n=====\n\ntext\n", "");
+
+        assert_eq!(html, "<h1>Title</h1>\n<p>text</p>\n");
+    }
+
+    #[test]
+    fn test_rst_lists() {
+        let html = render("- one\n- two\n  continued\n\n1. first\n2. second\n", "");
+
+        assert_eq!(
+            html,
+            "<ul>\n<li>one</li>\n<li>two continued</li>\n</ul>\n\
+             <ol>\n<li>first</li>\n<li>second</li>\n</ol>\n"
+        );
+    }
+
+    #[test]
+    fn test_rst_literal_blocks() {
+        let html = render(
+            "Example::\n\n    fn main() {}\n      <indented>\n\nAfter\n",
+            "",
+        );
+
+        assert_eq!(
+            html,
+            "<p>Example:</p>\n<pre><code>fn main() {}\n  &lt;indented&gt;</code></pre>\n<p>After</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_rst_code_block_directive() {
+        let html = render(".. code-block:: rust\n   :linenos:\n\n   let x = 1;\n", "");
+
+        assert_eq!(html, "<pre><code>let x = 1;</code></pre>\n");
+    }
+
+    #[test]
+    fn test_rst_links() {
+        let html = render(
+            "See `the docs <docs>`_, `guide <guide.rst>`_ and `site <https://radicle.xyz>`_.\n",
+            "",
+        );
+
+        assert_eq!(
+            html,
+            format!(
+                "<p>See <a href=\"/v1/projects/{urn}/tree/{commit}/docs\">the docs</a>, \
+                 <a href=\"/v1/projects/{urn}/raw/{commit}/guide.rst\">guide</a> and \
+                 <a href=\"https://radicle.xyz\">site</a>.</p>\n",
+                urn = URN,
+                commit = COMMIT
+            )
+        );
+    }
+
+    #[test]
+    fn test_links_relative_to_readme_dir() {
+        let urn = URN.parse::<Urn>().unwrap();
+        let is_tree = |_: &str| false;
+        let links = Links {
+            urn: &urn,
+            commit: git2::Oid::from_str(COMMIT).unwrap(),
+            dir: "docs",
+            is_tree: &is_tree,
+        };
+
+        assert_eq!(
+            links.rewrite("../img/logo.png#top", true),
+            format!("/v1/projects/{}/raw/{}/img/logo.png#top", URN, COMMIT)
+        );
+        assert_eq!(links.rewrite("../../escape", false), "../../escape");
+        assert_eq!(links.rewrite("#anchor", false), "#anchor");
+        assert_eq!(
+            links.rewrite("mailto:a@b.c", false),
+            "mailto:a@b.c".to_owned()
+        );
+    }
+}
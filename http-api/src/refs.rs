@@ -0,0 +1,68 @@
+//! Reading refs in project namespaces.
+use radicle_daemon::{PeerId, Urn};
+
+use crate::error::Error;
+
+/// List the local branches of a project, as `(name, oid)` pairs, from `refs/heads/*`.
+pub fn local_branches(
+    repo: &git2::Repository,
+    urn: &Urn,
+) -> Result<Vec<(String, git2::Oid)>, Error> {
+    let prefix = format!("refs/namespaces/{}/refs/heads/", urn.encode_id());
+
+    branches(repo, &prefix)
+}
+
+/// List a peer's branches in a project, as `(name, oid)` pairs, from
+/// `refs/remotes/<peer>/heads/*`.
+pub fn remote_branches(
+    repo: &git2::Repository,
+    urn: &Urn,
+    peer: &PeerId,
+) -> Result<Vec<(String, git2::Oid)>, Error> {
+    let prefix = format!(
+        "refs/namespaces/{}/refs/remotes/{}/heads/",
+        urn.encode_id(),
+        peer.default_encoding()
+    );
+    branches(repo, &prefix)
+}
+
+/// Get the head of a peer's branch in a project.
+pub fn remote_branch(
+    repo: &git2::Repository,
+    urn: &Urn,
+    peer: &PeerId,
+    name: &str,
+) -> Result<git2::Oid, Error> {
+    let refname = format!(
+        "refs/namespaces/{}/refs/remotes/{}/heads/{}",
+        urn.encode_id(),
+        peer.default_encoding(),
+        name
+    );
+    let commit = repo
+        .find_reference(&refname)
+        .and_then(|r| r.peel_to_commit())
+        .map_err(|_| Error::NotFound)?;
+
+    Ok(commit.id())
+}
+
+fn branches(repo: &git2::Repository, prefix: &str) -> Result<Vec<(String, git2::Oid)>, Error> {
+    let mut branches = Vec::new();
+
+    for reference in repo.references_glob(&format!("{}*", prefix))? {
+        let reference = reference?;
+        let name = match reference.name().and_then(|n| n.strip_prefix(prefix)) {
+            Some(name) => name.to_owned(),
+            None => continue,
+        };
+        if let Ok(commit) = reference.peel_to_commit() {
+            branches.push((name, commit.id()));
+        }
+    }
+    branches.sort();
+
+    Ok(branches)
+}
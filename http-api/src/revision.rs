@@ -0,0 +1,147 @@
+//! Revision resolution for source endpoints.
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use percent_encoding::percent_decode_str;
+use radicle_daemon::{PeerId, Urn};
+
+use crate::error::Error;
+
+/// A revision given in a request: a commit oid, a branch or tag name, a full ref name or `HEAD`.
+///
+/// When parsed from a path segment, the revision is percent-decoded, so that branch names
+/// containing slashes can be passed as eg. `feature%2Ffoo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revision(String);
+
+impl Revision {
+    /// Resolve the revision to a commit in the project's namespace.
+    ///
+    /// Names are resolved following the git rules (see `gitrevisions(7)`), relative to the
+    /// namespace, or relative to the peer's remote refs in the namespace if a peer is given:
+    ///
+    /// 1. `<name>`, if it is `HEAD` or starts with `refs/`
+    /// 2. `refs/<name>`
+    /// 3. `refs/tags/<name>`
+    /// 4. `refs/heads/<name>`
+    /// 5. `refs/remotes/<name>`
+    /// 6. `refs/remotes/<name>/HEAD`
+    ///
+    /// Full commit oids are tried before names, abbreviated ones after. Since all projects
+    /// share the monorepo object database, commits given by oid must be reachable from the
+    /// project's refs.
+    pub fn resolve(
+        &self,
+        repo: &git2::Repository,
+        urn: &Urn,
+        peer: Option<&PeerId>,
+    ) -> Result<git2::Oid, Error> {
+        let name = self.0.as_str();
+
+        if name.len() == 40 {
+            if let Ok(commit) = git2::Oid::from_str(name).and_then(|oid| repo.find_commit(oid)) {
+                return reachable(repo, urn, commit.id());
+            }
+        }
+
+        let namespace = format!("refs/namespaces/{}/", urn.encode_id());
+        let candidates = match peer {
+            // Remote refs are stored without the `refs/` prefix, eg.
+            // `refs/remotes/<peer>/heads/master`.
+            Some(peer) => {
+                let base = format!("{}refs/remotes/{}/", namespace, peer.default_encoding());
+                let mut candidates = Vec::new();
+
+                if let Some(name) = name.strip_prefix("refs/") {
+                    candidates.push(format!("{}{}", base, name));
+                }
+                // Includes `HEAD`.
+                candidates.push(format!("{}{}", base, name));
+                candidates.push(format!("{}tags/{}", base, name));
+                candidates.push(format!("{}heads/{}", base, name));
+                candidates
+            }
+            None => {
+                let mut candidates = Vec::new();
+
+                if name == "HEAD" || name.starts_with("refs/") {
+                    candidates.push(format!("{}{}", namespace, name));
+                }
+                candidates.push(format!("{}refs/{}", namespace, name));
+                candidates.push(format!("{}refs/tags/{}", namespace, name));
+                candidates.push(format!("{}refs/heads/{}", namespace, name));
+                candidates.push(format!("{}refs/remotes/{}", namespace, name));
+                candidates.push(format!("{}refs/remotes/{}/HEAD", namespace, name));
+                candidates
+            }
+        };
+
+        for candidate in candidates {
+            if let Ok(commit) = repo
+                .find_reference(&candidate)
+                .and_then(|r| r.peel_to_commit())
+            {
+                return Ok(commit.id());
+            }
+        }
+
+        // Abbreviated oids. We only hand hex strings to `revparse`, as it would otherwise
+        // resolve names outside of the project namespace.
+        if name.len() >= 4 && name.len() < 40 && name.chars().all(|c| c.is_ascii_hexdigit()) {
+            if let Ok(commit) = repo
+                .revparse_single(name)
+                .and_then(|object| object.peel_to_commit())
+            {
+                return reachable(repo, urn, commit.id());
+            }
+        }
+
+        Err(Error::NotFound)
+    }
+}
+
+/// Check that a commit is reachable from one of the refs of the project's namespace, so that
+/// objects of other projects can't be served through this one.
+fn reachable(repo: &git2::Repository, urn: &Urn, oid: git2::Oid) -> Result<git2::Oid, Error> {
+    let glob = format!("refs/namespaces/{}/refs/*", urn.encode_id());
+    let mut tips = HashSet::new();
+
+    for reference in repo.references_glob(&glob)? {
+        if let Ok(commit) = reference?.peel_to_commit() {
+            tips.insert(commit.id());
+        }
+    }
+    // Many refs share their tip, eg. the branches of peers that are up to date, and commits
+    // are often given by tip, so tips are checked before walking the graph.
+    if tips.contains(&oid) {
+        return Ok(oid);
+    }
+    for tip in tips {
+        if repo.graph_descendant_of(tip, oid)? {
+            return Ok(oid);
+        }
+    }
+    Err(Error::NotFound)
+}
+
+impl FromStr for Revision {
+    type Err = std::str::Utf8Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let decoded = percent_decode_str(s).decode_utf8()?;
+
+        Ok(Self(decoded.into_owned()))
+    }
+}
+
+impl From<String> for Revision {
+    fn from(other: String) -> Self {
+        Self(other)
+    }
+}
+
+impl std::fmt::Display for Revision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
@@ -0,0 +1,120 @@
+//! Commit signature verification.
+//!
+//! SSH signatures made with ed25519 keys, which is what radicle peers use, are verified, see
+//! [`shared::ssh`]. GPG signatures are reported but not verified, as we don't have access to a
+//! keyring.
+use radicle_daemon::PeerId;
+use serde::Serialize;
+use shared::ssh;
+
+const GPG_ARMOR: &str = "-----BEGIN PGP SIGNATURE-----";
+
+/// Signature type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Ssh,
+    Gpg,
+}
+
+/// Verification status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Valid,
+    Invalid,
+    /// The signature couldn't be verified, eg. because of an unsupported key type.
+    Unknown,
+}
+
+impl From<ssh::Status> for Status {
+    fn from(status: ssh::Status) -> Self {
+        match status {
+            ssh::Status::Valid => Self::Valid,
+            ssh::Status::Invalid => Self::Invalid,
+            ssh::Status::Unknown => Self::Unknown,
+        }
+    }
+}
+
+/// Radicle identity matching the signing key.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Signer {
+    pub peer: PeerId,
+    pub name: Option<String>,
+    /// Whether the signer is a project delegate, as opposed to a tracked peer.
+    pub delegate: bool,
+}
+
+/// A commit signature.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Signature {
+    #[serde(rename = "type")]
+    pub kind: Kind,
+    /// SSH key fingerprint, eg. `SHA256:...`.
+    pub fingerprint: Option<String>,
+    pub status: Status,
+    /// Radicle identity of the signer, if the signature is valid.
+    pub signer: Option<Signer>,
+}
+
+impl Signature {
+    /// Get and verify the signature of a commit, if it is signed. Candidate signers are
+    /// matched against the signing key, once the signature is verified.
+    pub fn load(
+        repo: &git2::Repository,
+        oid: git2::Oid,
+        signers: &[Signer],
+    ) -> Result<Option<Self>, git2::Error> {
+        let (signature, data) = match repo.extract_signature(&oid, None) {
+            Ok(s) => s,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let signature = String::from_utf8_lossy(&signature);
+
+        if signature.trim_start().starts_with(GPG_ARMOR) {
+            return Ok(Some(Self {
+                kind: Kind::Gpg,
+                fingerprint: None,
+                status: Status::Unknown,
+                signer: None,
+            }));
+        }
+        if !signature.trim_start().starts_with(ssh::ARMOR) {
+            return Ok(None);
+        }
+
+        let sig = match ssh::Signature::from_armored(&signature) {
+            Some(sig) => sig,
+            None => {
+                return Ok(Some(Self {
+                    kind: Kind::Ssh,
+                    fingerprint: None,
+                    status: Status::Invalid,
+                    signer: None,
+                }))
+            }
+        };
+        let fingerprint = sig.fingerprint();
+        let status = Status::from(sig.verify(&data));
+        // Only a valid signature proves who signed the commit.
+        let signer = if status == Status::Valid {
+            signers
+                .iter()
+                .find(|s| ssh::peer_fingerprint(&s.peer) == fingerprint)
+                .cloned()
+        } else {
+            None
+        };
+
+        Ok(Some(Self {
+            kind: Kind::Ssh,
+            fingerprint: Some(fingerprint),
+            status,
+            signer,
+        }))
+    }
+}
@@ -217,6 +217,13 @@ impl Client {
             storage,
         };
         let peer = Peer::new(peer_config).expect("signing key must match peer id");
+        // Keep reflogs for namespaced refs, which git otherwise doesn't log in bare
+        // repositories, so that the http-api can read project activity from them.
+        if let Err(e) = git2::Config::open(&self.paths.git_dir().join("config"))
+            .and_then(|mut config| config.set_str("core.logAllRefUpdates", "always"))
+        {
+            tracing::warn!(target: "org-node", err = ?e, "Failed to enable reflogs, project activity will be incomplete");
+        }
         let mut requests = ReceiverStream::new(self.requests).fuse();
 
         // Establish Peer Tracking Channel for protocol listener and request handler.
@@ -766,17 +773,32 @@ impl Client {
 
         tracing::debug!(target: "org-node", "Setting ref {:?} -> {:?}", &local_branch_ref, oid);
         repository
-            .reference(local_branch_ref, oid, true, "set-local-branch (org-node)")
+            .reference(
+                local_branch_ref,
+                oid,
+                true,
+                &format!("set-local-branch (org-node) {}", maintainer),
+            )
             .map_err(|e| Error::SetHead(Box::new(e)))?;
 
         tracing::debug!(target: "org-node", "Setting ref {:?} -> {:?}", &branch_ref, oid);
         repository
-            .reference(&branch_ref, oid, true, "set-remote-branch (org-node)")
+            .reference(
+                &branch_ref,
+                oid,
+                true,
+                &format!("set-remote-branch (org-node) {}", maintainer),
+            )
             .map_err(|e| Error::SetHead(Box::new(e)))?;
 
         tracing::debug!(target: "org-node", "Setting ref {:?} -> {:?}", &head, local_branch_ref);
         repository
-            .reference_symbolic(head, local_branch_ref, true, "set-head (org-node)")
+            .reference_symbolic(
+                head,
+                local_branch_ref,
+                true,
+                &format!("set-head (org-node) {}", maintainer),
+            )
             .map_err(|e| Error::SetHead(Box::new(e)))?;
 
         Ok(Rev::Git(oid))
@@ -18,12 +18,12 @@ use futures::StreamExt;
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::{ReceiverStream, UnboundedReceiverStream};
 
-use std::collections::VecDeque;
+use std::collections::{BTreeSet, VecDeque};
 use std::convert::TryInto;
 use std::io::{self, BufRead, BufReader};
-use std::net;
 use std::os::unix::net::UnixListener;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::{fs, net};
 
 mod client;
 mod error;
@@ -70,7 +70,6 @@ struct Project {
     #[allow(dead_code)]
     timestamp: u64,
     anchor: Anchor,
-    #[allow(dead_code)]
     org: Org,
 }
 
@@ -122,7 +121,6 @@ struct Anchor {
 
 #[derive(serde::Deserialize, Debug)]
 struct Org {
-    #[allow(dead_code)]
     id: OrgId,
 }
 
@@ -186,6 +184,7 @@ pub fn run(rt: tokio::runtime::Runtime, options: Options) -> anyhow::Result<()>
         .signer()
         .with_context(|| format!("unable to load identity {:?}", &identity_path))?;
     let peer_id = PeerId::from(signer.clone());
+    let anchors = paths.git_dir().join(project_index::policy::ANCHORS_FILE);
     let client = Client::new(
         paths,
         signer,
@@ -237,7 +236,7 @@ pub fn run(rt: tokio::runtime::Runtime, options: Options) -> anyhow::Result<()>
 
     // First get up to speed with existing anchors, before we start listening for events.
     let projects = query(&options.subgraph, timestamp, &addresses).map_err(Box::new)?;
-    rt.block_on(process_anchors(projects, &work))?;
+    rt.block_on(process_anchors(projects, &anchors, &work))?;
     rt.block_on(process_urns(options.urns.clone(), &work))?;
 
     // Now launch the event subscriber and listen on events.
@@ -250,6 +249,7 @@ pub fn run(rt: tokio::runtime::Runtime, options: Options) -> anyhow::Result<()>
     let query_task = rt.spawn(query_projects(
         timestamp,
         options.subgraph.clone(),
+        anchors,
         events,
         work,
     ));
@@ -275,13 +275,14 @@ pub fn run(rt: tokio::runtime::Runtime, options: Options) -> anyhow::Result<()>
 async fn query_projects(
     timestamp: u64,
     subgraph: String,
+    anchors: PathBuf,
     mut events: mpsc::Receiver<Log>,
     work: mpsc::Sender<Urn>,
 ) {
     while let Some(event) = events.recv().await {
         match query(&subgraph, timestamp, &[event.address]) {
             Ok(projects) => {
-                if let Err(err) = process_anchors(projects, &work).await {
+                if let Err(err) = process_anchors(projects, &anchors, &work).await {
                     tracing::error!(target: "org-node", "Anchor processing failed: {}", err);
                     return;
                 }
@@ -309,12 +310,18 @@ async fn process_urns(urns: Vec<Urn>, work: &mpsc::Sender<Urn>) -> Result<(), Er
     Ok(())
 }
 
-async fn process_anchors(projects: Vec<Project>, work: &mpsc::Sender<Urn>) -> Result<(), Error> {
+async fn process_anchors(
+    projects: Vec<Project>,
+    anchors: &Path,
+    work: &mpsc::Sender<Urn>,
+) -> Result<(), Error> {
     if projects.is_empty() {
         return Ok(());
     }
     tracing::info!(target: "org-node", "Found {} project(s)", projects.len());
 
+    let mut recorded = Vec::with_capacity(projects.len());
+
     for project in projects {
         tracing::debug!(target: "org-node", "{:?}", project);
 
@@ -326,12 +333,46 @@ async fn process_anchors(projects: Vec<Project>, work: &mpsc::Sender<Urn>) -> Re
             }
         };
 
+        recorded.push((project.org.id.clone(), urn.clone()));
+
         tracing::info!(target: "org-node", "Queueing {}", urn);
         work.send(urn).await?;
     }
+    // Org policies of the other services rely on these, but failing to record them
+    // shouldn't stop replication.
+    if let Err(err) = record_anchors(anchors, &recorded) {
+        tracing::error!(target: "org-node", "Failed to record anchors: {}", err);
+    }
     Ok(())
 }
 
+/// Record which org anchored each project, as `<org> <urn>` lines. See
+/// [`project_index::policy`].
+fn record_anchors(path: &Path, anchors: &[(OrgId, Urn)]) -> io::Result<()> {
+    let mut lines = match fs::read_to_string(path) {
+        Ok(contents) => contents.lines().map(String::from).collect(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => BTreeSet::new(),
+        Err(err) => return Err(err),
+    };
+    let len = lines.len();
+
+    lines.extend(
+        anchors
+            .iter()
+            .map(|(org, urn)| format!("{} {}", org.to_lowercase(), urn)),
+    );
+    if lines.len() == len {
+        return Ok(());
+    }
+    // Write atomically, since the file is read by other services.
+    let tmp = path.with_extension("tmp");
+    fs::write(
+        &tmp,
+        lines.into_iter().map(|l| l + "\n").collect::<String>(),
+    )?;
+    fs::rename(tmp, path)
+}
+
 /// Get projects updated or created since the given timestamp, from the given orgs.
 /// If no org is specified, gets projects from *all* orgs.
 fn query(subgraph: &str, timestamp: u64, orgs: &[Address]) -> Result<Vec<Project>, ureq::Error> {
@@ -0,0 +1,135 @@
+//! Project aliases.
+//!
+//! Projects can be referred to by name instead of URN. Since names aren't unique, aliases
+//! are resolved as follows:
+//!
+//! 1. Aliases from the operator's alias file always win.
+//! 2. Every project is aliased as `<delegate-name>~<project-name>` for each of its
+//!    delegates that is a personal identity. The separator keeps aliases to a single path
+//!    segment, so that they can be used in URLs wherever URNs are.
+//! 3. A project is aliased as `<project-name>` if no other project has that name.
+//!
+//! Aliases claimed by more than one project resolve to none of them, and are reported as
+//! collisions instead. Resolution doesn't depend on the order projects are listed in.
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::{fs, io};
+
+use librad::git::Urn;
+
+/// Separator of delegate and project names in qualified aliases.
+pub const SEPARATOR: char = '~';
+
+/// A project that can be aliased.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub urn: Urn,
+    pub name: String,
+    /// Names of the project's personal identity delegates.
+    pub delegates: Vec<String>,
+}
+
+/// Resolved aliases.
+#[derive(Debug, Default, Clone)]
+pub struct Aliases {
+    aliases: BTreeMap<String, Urn>,
+    collisions: BTreeMap<String, Vec<Urn>>,
+}
+
+impl Aliases {
+    /// Compute the aliases of the given projects. `overrides` maps aliases to URNs, and takes
+    /// precedence over project names.
+    pub fn build(
+        candidates: impl IntoIterator<Item = Candidate>,
+        overrides: BTreeMap<String, Urn>,
+    ) -> Self {
+        // Claimed aliases, with the claiming projects keyed by URN, for a stable order.
+        let mut claims: BTreeMap<String, BTreeMap<String, Urn>> = BTreeMap::new();
+
+        for candidate in candidates {
+            let id = candidate.urn.to_string();
+
+            for delegate in &candidate.delegates {
+                claims
+                    .entry(format!("{}{}{}", delegate, SEPARATOR, candidate.name))
+                    .or_default()
+                    .insert(id.clone(), candidate.urn.clone());
+            }
+            claims
+                .entry(candidate.name)
+                .or_default()
+                .insert(id, candidate.urn);
+        }
+
+        let mut aliases = BTreeMap::new();
+        let mut collisions = BTreeMap::new();
+
+        for (alias, urns) in claims {
+            if overrides.contains_key(&alias) {
+                continue;
+            }
+            let mut urns = urns.into_values().collect::<Vec<_>>();
+
+            if urns.len() == 1 {
+                aliases.insert(alias, urns.remove(0));
+            } else {
+                collisions.insert(alias, urns);
+            }
+        }
+        aliases.extend(overrides);
+
+        Self {
+            aliases,
+            collisions,
+        }
+    }
+
+    /// Resolve an alias.
+    pub fn resolve(&self, alias: &str) -> Option<&Urn> {
+        self.aliases.get(alias)
+    }
+
+    /// All resolvable aliases.
+    pub fn aliases(&self) -> &BTreeMap<String, Urn> {
+        &self.aliases
+    }
+
+    /// Aliases claimed by more than one project, with the projects claiming them.
+    pub fn collisions(&self) -> &BTreeMap<String, Vec<Urn>> {
+        &self.collisions
+    }
+}
+
+/// Load an alias file. Each line holds an alias and a URN, separated by whitespace. Empty
+/// lines and lines starting with `#` are ignored.
+pub fn load(path: &Path) -> io::Result<BTreeMap<String, Urn>> {
+    let contents = fs::read_to_string(path)?;
+    let mut overrides = BTreeMap::new();
+
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let (alias, urn) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(alias), Some(urn), None) => (alias, urn),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}:{}: expected an alias and a URN", path.display(), i + 1),
+                ))
+            }
+        };
+        let urn = Urn::from_str(urn).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}:{}: {}", path.display(), i + 1, e),
+            )
+        })?;
+        overrides.insert(alias.to_owned(), urn);
+    }
+    Ok(overrides)
+}
@@ -0,0 +1,752 @@
+//! Index of the projects and persons in the monorepo, shared by the services.
+//!
+//! Identities are loaded once and kept in memory. On refresh, the refs and tracked peers of all
+//! namespaces are scanned and only the namespaces that changed are loaded again. Refreshes can
+//! be triggered by filesystem notifications on the monorepo refs, see [`Index::watch`].
+//!
+//! The index also enforces the seed's [`policy::Policy`]: denied projects are left out of
+//! listings and aliases, and services are expected to check [`Index::allows`] before serving
+//! a project, or [`Index::can_read`] when the reader is known, for private projects.
+pub mod aliases;
+pub mod policy;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex, RwLock, RwLockReadGuard};
+use std::time::{Duration, SystemTime};
+use std::{fs, io, thread};
+
+use either::Either;
+use librad::git::identities;
+use librad::git::storage::ReadOnly;
+use librad::git::{tracking, Urn};
+use librad::identities::SomeIdentity;
+use librad::paths::Paths;
+use librad::PeerId;
+use notify::Watcher as _;
+use sha2::Digest;
+
+use crate::aliases::{Aliases, Candidate};
+use crate::policy::Policy;
+
+const NAMESPACES: &str = "refs/namespaces/";
+/// Delay for which filesystem events are collected before refreshing.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Index errors.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+
+    #[error(transparent)]
+    Identities(#[from] identities::Error),
+
+    #[error(transparent)]
+    Init(#[from] librad::git::storage::read::error::Init),
+
+    #[error(transparent)]
+    Notify(#[from] notify::Error),
+
+    #[error("invalid policy file: {0}")]
+    Policy(io::Error),
+
+    #[error("invalid alias file: {0}")]
+    Aliases(io::Error),
+}
+
+/// Index configuration.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// Operator alias file, see [`aliases::load`].
+    pub alias_file: Option<PathBuf>,
+    /// Policy file deciding which projects are served, see [`policy::Policy::load`].
+    pub policy_file: Option<PathBuf>,
+}
+
+/// An indexed project.
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub urn: Urn,
+    pub name: String,
+    pub description: Option<String>,
+    pub default_branch: Option<String>,
+    /// Peers acting as delegates, directly or on behalf of a person.
+    pub delegates: Vec<PeerId>,
+    /// Delegations of the project identity.
+    pub delegations: Vec<Delegation>,
+    /// Head of the local default branch, if any.
+    pub head: Option<git2::Oid>,
+    /// Time of the first identity revision, in seconds since epoch.
+    pub created: i64,
+    /// Time of the most recent commit in the namespace, in seconds since epoch.
+    pub updated: i64,
+    /// Commits reachable from the head.
+    pub commits: usize,
+    /// Local branches.
+    pub branches: usize,
+    /// Peers tracked in the project.
+    pub tracked: Vec<PeerId>,
+}
+
+/// A project delegation.
+#[derive(Debug, Clone)]
+pub enum Delegation {
+    /// Direct delegation, ie. public key.
+    Direct(PeerId),
+    /// Indirect delegation, ie. a personal identity, with the peers acting on its behalf.
+    Indirect { urn: Urn, peers: Vec<PeerId> },
+}
+
+/// How a peer relates to a project.
+#[derive(Debug, Clone)]
+pub enum Relationship {
+    /// The peer is a delegate of the project.
+    Delegate,
+    /// The peer acts on behalf of a personal identity that is a delegate of the project.
+    IndirectDelegate(Urn),
+    /// The project tracks the peer.
+    Tracked,
+}
+
+impl Project {
+    /// How a peer relates to the project, if at all.
+    pub fn relationships(&self, peer: &PeerId) -> Vec<Relationship> {
+        let mut relationships = self
+            .delegations
+            .iter()
+            .filter_map(|delegation| match delegation {
+                Delegation::Direct(id) if id == peer => Some(Relationship::Delegate),
+                Delegation::Indirect { urn, peers } if peers.contains(peer) => {
+                    Some(Relationship::IndirectDelegate(urn.clone()))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        if self.tracked.contains(peer) {
+            relationships.push(Relationship::Tracked);
+        }
+        relationships
+    }
+}
+
+/// An indexed person.
+#[derive(Debug, Clone)]
+pub struct Person {
+    pub urn: Urn,
+    pub name: String,
+    pub delegates: Vec<PeerId>,
+}
+
+/// An indexed identity.
+#[derive(Debug, Clone)]
+enum Identity {
+    Project {
+        project: Project,
+        /// Names of the personal identity delegates, for aliasing.
+        delegate_names: Vec<String>,
+    },
+    Person(Person),
+    /// Not a known identity, eg. a namespace without a valid identity.
+    None,
+}
+
+#[derive(Default)]
+struct State {
+    /// Indexed namespaces, with the digest of their refs as of the last refresh.
+    namespaces: HashMap<String, (String, Identity)>,
+    aliases: Aliases,
+    /// Aliases from the alias file.
+    overrides: BTreeMap<String, Urn>,
+    /// Modification time of the alias file when it was last loaded.
+    alias_file_modified: Option<SystemTime>,
+    policy: Policy,
+    /// Modification time of the policy file when it was last loaded.
+    policy_file_modified: Option<SystemTime>,
+    /// Orgs that anchored each project.
+    anchors: HashMap<Urn, HashSet<String>>,
+    /// Modification time of the anchors file when it was last loaded.
+    anchors_modified: Option<SystemTime>,
+}
+
+impl State {
+    /// Whether the policy allows serving the given namespace.
+    fn allows(&self, urn: &Urn, identity: Option<&Identity>) -> bool {
+        let no_orgs = HashSet::new();
+        let orgs = self.anchors.get(urn).unwrap_or(&no_orgs);
+
+        match identity {
+            Some(Identity::Project { project, .. }) => {
+                self.policy.allows(urn, &project.delegates, orgs)
+            }
+            Some(Identity::Person(person)) => self.policy.allows(urn, &person.delegates, orgs),
+            Some(Identity::None) | None => self.policy.allows(urn, &[], orgs),
+        }
+    }
+
+    /// Whether the given namespace may be served to a reader.
+    fn can_read(&self, urn: &Urn, identity: Option<&Identity>, reader: Option<&PeerId>) -> bool {
+        let delegates = match identity {
+            Some(Identity::Project { project, .. }) => project.delegates.as_slice(),
+            Some(Identity::Person(person)) => person.delegates.as_slice(),
+            Some(Identity::None) | None => &[],
+        };
+        self.allows(urn, identity) && self.policy.can_read(urn, delegates, reader)
+    }
+}
+
+/// A read-only view of the index, see [`Index::snapshot`].
+pub struct Snapshot<'a>(RwLockReadGuard<'a, State>);
+
+impl Snapshot<'_> {
+    /// Digest of a namespace's refs and tracked peers as of the last refresh. The digest
+    /// changes whenever anything indexed about the namespace does, so it can be used to
+    /// cache data derived from it.
+    pub fn digest(&self, urn: &Urn) -> Option<String> {
+        self.0
+            .namespaces
+            .get(&urn.encode_id())
+            .map(|(digest, _)| digest.clone())
+    }
+
+    /// Whether the given project or person may be served to a reader, see
+    /// [`Index::can_read`].
+    pub fn can_read(&self, urn: &Urn, reader: Option<&PeerId>) -> bool {
+        let identity = self.0.namespaces.get(&urn.encode_id()).map(|(_, i)| i);
+
+        self.0.can_read(urn, identity, reader)
+    }
+
+    /// Resolve a project alias. Aliases of denied projects don't resolve.
+    pub fn resolve_alias(&self, alias: &str) -> Option<Urn> {
+        let urn = self.0.aliases.resolve(alias)?;
+        let identity = self.0.namespaces.get(&urn.encode_id()).map(|(_, i)| i);
+
+        self.0.allows(urn, identity).then(|| urn.clone())
+    }
+}
+
+/// Project index. Cloning the index is cheap, clones share their state.
+#[derive(Clone)]
+pub struct Index {
+    paths: Paths,
+    config: Config,
+    state: Arc<RwLock<State>>,
+    /// Held while refreshing, so that concurrent refreshes don't duplicate work.
+    refreshing: Arc<Mutex<()>>,
+}
+
+impl Index {
+    /// Create an empty index. Call [`Index::refresh`] to populate it.
+    pub fn new(paths: Paths, config: Config) -> Self {
+        Self {
+            paths,
+            config,
+            state: Default::default(),
+            refreshing: Default::default(),
+        }
+    }
+
+    /// Get a project.
+    pub fn project(&self, urn: &Urn) -> Option<Project> {
+        match self.get(urn)? {
+            Identity::Project { project, .. } => Some(project),
+            _ => None,
+        }
+    }
+
+    /// Get a person.
+    pub fn person(&self, urn: &Urn) -> Option<Person> {
+        match self.get(urn)? {
+            Identity::Person(person) => Some(person),
+            _ => None,
+        }
+    }
+
+    /// All projects allowed by the policy, in no particular order.
+    pub fn projects(&self) -> Vec<Project> {
+        let state = self.state.read().expect("index lock is not poisoned");
+
+        state
+            .namespaces
+            .values()
+            .filter_map(|(_, identity)| match identity {
+                Identity::Project { project, .. } if state.allows(&project.urn, Some(identity)) => {
+                    Some(project.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Projects allowed by the policy that a peer is related to, with the relationships that
+    /// apply, in no particular order.
+    pub fn by_peer(&self, peer: &PeerId) -> Vec<(Project, Vec<Relationship>)> {
+        self.projects()
+            .into_iter()
+            .filter_map(|project| {
+                let relationships = project.relationships(peer);
+
+                (!relationships.is_empty()).then(|| (project, relationships))
+            })
+            .collect()
+    }
+
+    /// Digest of a namespace's refs and tracked peers as of the last refresh, see
+    /// [`Snapshot::digest`].
+    pub fn digest(&self, urn: &Urn) -> Option<String> {
+        self.snapshot().digest(urn)
+    }
+
+    /// Whether a namespace is indexed, whether or not it holds a known identity.
+    pub fn contains(&self, urn: &Urn) -> bool {
+        let state = self.state.read().expect("index lock is not poisoned");
+
+        state.namespaces.contains_key(&urn.encode_id())
+    }
+
+    /// Whether the policy allows serving the given project or person. Namespaces that aren't
+    /// indexed have no known delegates, so delegate rules don't apply to them, see [`policy`].
+    pub fn allows(&self, urn: &Urn) -> bool {
+        let state = self.state.read().expect("index lock is not poisoned");
+        let identity = state.namespaces.get(&urn.encode_id()).map(|(_, i)| i);
+
+        state.allows(urn, identity)
+    }
+
+    /// Whether the given project or person may be served to a reader, or to unauthenticated
+    /// readers if no reader is given. Private projects are only served to their readers and
+    /// delegates.
+    pub fn can_read(&self, urn: &Urn, reader: Option<&PeerId>) -> bool {
+        self.snapshot().can_read(urn, reader)
+    }
+
+    /// Whether a project is private, ie. only served to some readers.
+    pub fn is_private(&self, urn: &Urn) -> bool {
+        let state = self.state.read().expect("index lock is not poisoned");
+
+        state.policy.is_private(urn)
+    }
+
+    /// Resolve a project alias. Aliases of denied projects don't resolve.
+    pub fn resolve_alias(&self, alias: &str) -> Option<Urn> {
+        self.snapshot().resolve_alias(alias)
+    }
+
+    /// Project aliases.
+    pub fn aliases(&self) -> Aliases {
+        let state = self.state.read().expect("index lock is not poisoned");
+
+        state.aliases.clone()
+    }
+
+    /// A consistent view of the index, for lookups that must see the same refresh. Refreshes
+    /// wait until the snapshot is dropped, so it shouldn't be held for long.
+    pub fn snapshot(&self) -> Snapshot<'_> {
+        Snapshot(self.state.read().expect("index lock is not poisoned"))
+    }
+
+    fn get(&self, urn: &Urn) -> Option<Identity> {
+        let state = self.state.read().expect("index lock is not poisoned");
+
+        state
+            .namespaces
+            .get(&urn.encode_id())
+            .map(|(_, identity)| identity.clone())
+    }
+
+    /// Bring the index up to date with the monorepo. Returns whether anything changed.
+    pub fn refresh(&self) -> Result<bool, Error> {
+        let _guard = self.refreshing.lock().expect("index lock is not poisoned");
+        let repo = git2::Repository::open_bare(self.paths.git_dir())?;
+        let storage = ReadOnly::open(&self.paths)?;
+        let mut digests = digests(&repo)?;
+
+        // Tracking isn't necessarily recorded under the namespace refs, so the tracked peers
+        // are part of the digest, for their counts to stay current.
+        for (id, digest) in digests.iter_mut() {
+            if let Ok(urn) = Urn::try_from_id(id) {
+                let mut hasher = sha2::Sha256::new();
+
+                hasher.update(digest.as_bytes());
+                for peer in tracked(&storage, &urn) {
+                    hasher.update(peer.to_string().as_bytes());
+                }
+                *digest = format!("{:x}", hasher.finalize());
+            }
+        }
+
+        // Find what changed without holding the write lock, since loading identities is slow.
+        let policy_file_modified = self.config.policy_file.as_deref().and_then(modified);
+        let alias_file_modified = self.config.alias_file.as_deref().and_then(modified);
+        let (removed, stale, policy, overrides) = {
+            let state = self.state.read().expect("index lock is not poisoned");
+            let removed = state
+                .namespaces
+                .keys()
+                .filter(|id| !digests.contains_key(*id))
+                .cloned()
+                .collect::<Vec<_>>();
+            // Stale namespaces, with the head and commit count they were last indexed with,
+            // so that commits don't have to be counted from scratch.
+            let stale = digests
+                .into_iter()
+                .filter_map(|(id, digest)| match state.namespaces.get(&id) {
+                    Some((current, _)) if *current == digest => None,
+                    Some((_, Identity::Project { project, .. })) => {
+                        let previous = project.head.map(|head| (head, project.commits));
+                        Some((id, digest, previous))
+                    }
+                    _ => Some((id, digest, None)),
+                })
+                .collect::<Vec<_>>();
+            // An invalid policy fails the refresh, leaving the previous policy in effect.
+            let policy = match &self.config.policy_file {
+                Some(path) if policy_file_modified != state.policy_file_modified => {
+                    Some(Policy::load(path).map_err(Error::Policy)?)
+                }
+                _ => None,
+            };
+            // Likewise for the alias file.
+            let overrides = match &self.config.alias_file {
+                Some(path) if alias_file_modified != state.alias_file_modified => {
+                    Some(aliases::load(path).map_err(Error::Aliases)?)
+                }
+                _ => None,
+            };
+
+            (removed, stale, policy, overrides)
+        };
+        let mut loaded = Vec::with_capacity(stale.len());
+        for (id, digest, previous) in stale {
+            // Namespaces that fail to load are left as they were, and retried on the next
+            // refresh since their digest is still stale.
+            match load(&repo, &storage, &id, previous) {
+                Ok(identity) => loaded.push((id, digest, identity)),
+                Err(e) => tracing::warn!("Failed to index namespace {}: {}", id, e),
+            }
+        }
+        let anchors_file = self.paths.git_dir().join(policy::ANCHORS_FILE);
+        let anchors_modified = modified(&anchors_file);
+
+        let mut state = self.state.write().expect("index lock is not poisoned");
+        let mut changed = !removed.is_empty() || !loaded.is_empty();
+
+        for id in removed {
+            state.namespaces.remove(&id);
+        }
+        for (id, digest, identity) in loaded {
+            state.namespaces.insert(id, (digest, identity));
+        }
+        if let Some(policy) = policy {
+            state.policy = policy;
+            state.policy_file_modified = policy_file_modified;
+            changed = true;
+        }
+        if anchors_modified != state.anchors_modified {
+            state.anchors = policy::load_anchors(&anchors_file).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring anchors file {:?}: {}", anchors_file, e);
+                Default::default()
+            });
+            state.anchors_modified = anchors_modified;
+            changed = true;
+        }
+        if let Some(overrides) = overrides {
+            state.overrides = overrides;
+            state.alias_file_modified = alias_file_modified;
+            changed = true;
+        }
+        if changed {
+            state.aliases = build_aliases(&state);
+        }
+        Ok(changed)
+    }
+
+    /// Refresh the index whenever the monorepo refs change, from a background thread.
+    pub fn watch(&self) -> Result<thread::JoinHandle<()>, Error> {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::watcher(tx, DEBOUNCE)?;
+        let git_dir = self.paths.git_dir().to_path_buf();
+
+        // Loose refs, and the packed refs file at the top of the repository.
+        watcher.watch(git_dir.join("refs"), notify::RecursiveMode::Recursive)?;
+        watcher.watch(&git_dir, notify::RecursiveMode::NonRecursive)?;
+
+        // The alias and policy files are watched through their directories, since editors
+        // and tools usually replace files by renaming a new file over them, which would end
+        // a watch on the file itself.
+        let files = [&self.config.alias_file, &self.config.policy_file]
+            .iter()
+            .copied()
+            .flatten()
+            .collect::<Vec<_>>();
+        let mut dirs = HashSet::new();
+        for file in &files {
+            let dir = match file.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            if dirs.insert(dir) {
+                watcher.watch(dir, notify::RecursiveMode::NonRecursive)?;
+            }
+        }
+        let names = files
+            .iter()
+            .filter_map(|f| f.file_name())
+            .map(|n| n.to_owned())
+            .collect::<HashSet<_>>();
+
+        let index = self.clone();
+        let handle = thread::spawn(move || {
+            // Keep the watcher alive for as long as events are handled.
+            let _watcher = watcher;
+            // Other files in the directories of the alias and policy files are ignored.
+            let relevant = |path: &Path| {
+                path.starts_with(&git_dir) || path.file_name().is_some_and(|n| names.contains(n))
+            };
+
+            for event in rx {
+                let paths = match &event {
+                    notify::DebouncedEvent::Error(e, path) => {
+                        tracing::warn!("Index watcher error on {:?}: {}", path, e);
+                        continue;
+                    }
+                    notify::DebouncedEvent::Rescan => vec![],
+                    notify::DebouncedEvent::NoticeWrite(path)
+                    | notify::DebouncedEvent::NoticeRemove(path)
+                    | notify::DebouncedEvent::Create(path)
+                    | notify::DebouncedEvent::Write(path)
+                    | notify::DebouncedEvent::Chmod(path)
+                    | notify::DebouncedEvent::Remove(path) => vec![path],
+                    notify::DebouncedEvent::Rename(from, to) => vec![from, to],
+                };
+                if !paths.is_empty() && !paths.iter().any(|p| relevant(p)) {
+                    continue;
+                }
+                match index.refresh() {
+                    Ok(true) => tracing::debug!("Index refreshed"),
+                    Ok(false) => {}
+                    Err(e) => tracing::error!("Index refresh failed: {}", e),
+                }
+            }
+        });
+        Ok(handle)
+    }
+}
+
+/// Compute the aliases of the indexed projects allowed by the policy.
+fn build_aliases(state: &State) -> Aliases {
+    let candidates = state
+        .namespaces
+        .values()
+        .filter_map(|(_, identity)| match identity {
+            Identity::Project {
+                project,
+                delegate_names,
+            } if state.allows(&project.urn, Some(identity)) => Some(Candidate {
+                urn: project.urn.clone(),
+                name: project.name.clone(),
+                delegates: delegate_names.clone(),
+            }),
+            _ => None,
+        });
+    let aliases = Aliases::build(candidates, state.overrides.clone());
+
+    for (alias, urns) in aliases.collisions() {
+        tracing::warn!(
+            "Alias {:?} is ambiguous between {:?}, skipping",
+            alias,
+            urns
+        );
+    }
+    aliases
+}
+
+/// Modification time of a file, if it exists.
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Compute a digest of the refs of each namespace, keyed by namespace id. A namespace's
+/// digest changes whenever any of its refs is created, updated or deleted.
+pub fn digests(repo: &git2::Repository) -> Result<HashMap<String, String>, Error> {
+    let mut hashers: HashMap<String, sha2::Sha256> = HashMap::new();
+
+    for reference in repo.references_glob(&format!("{}*", NAMESPACES))? {
+        let reference = reference?;
+        let (name, target) = match (reference.name(), reference.target()) {
+            (Some(name), Some(target)) => (name, target),
+            _ => continue,
+        };
+        let id = match name
+            .strip_prefix(NAMESPACES)
+            .and_then(|n| n.split('/').next())
+        {
+            Some(id) => id,
+            None => continue,
+        };
+        let hasher = hashers.entry(id.to_owned()).or_default();
+
+        hasher.update(name.as_bytes());
+        hasher.update(target.as_bytes());
+    }
+
+    Ok(hashers
+        .into_iter()
+        .map(|(id, hasher)| (id, format!("{:x}", hasher.finalize())))
+        .collect())
+}
+
+/// Load the identity of a namespace. The head and commit count of a previous load, if any,
+/// are used to count the commits of the project incrementally.
+fn load(
+    repo: &git2::Repository,
+    storage: &ReadOnly,
+    id: &str,
+    previous: Option<(git2::Oid, usize)>,
+) -> Result<Identity, Error> {
+    let urn = match Urn::try_from_id(id) {
+        Ok(urn) => urn,
+        Err(_) => return Ok(Identity::None),
+    };
+    let identity = match identities::any::get(storage, &urn) {
+        Ok(Some(identity)) => identity,
+        Ok(None) => return Ok(Identity::None),
+        Err(e) => {
+            tracing::warn!("Failed to load identity {}: {}", urn, e);
+            return Ok(Identity::None);
+        }
+    };
+
+    match identity {
+        SomeIdentity::Project(doc) => {
+            let subject = doc.subject();
+            let default_branch = subject.default_branch.as_ref().map(|b| b.to_string());
+            let head = default_branch.as_ref().and_then(|branch| {
+                repo.find_reference(&format!("{}{}/refs/heads/{}", NAMESPACES, id, branch))
+                    .and_then(|r| r.peel_to_commit())
+                    .map(|c| c.id())
+                    .ok()
+            });
+            let mut delegates = Vec::new();
+            let mut delegations = Vec::new();
+            let mut delegate_names = Vec::new();
+
+            for delegation in doc.delegations().iter() {
+                match delegation {
+                    Either::Left(pk) => {
+                        delegates.push(PeerId::from(*pk));
+                        delegations.push(Delegation::Direct(PeerId::from(*pk)));
+                    }
+                    Either::Right(indirect) => {
+                        let peers = indirect
+                            .delegations()
+                            .iter()
+                            .cloned()
+                            .map(PeerId::from)
+                            .collect::<Vec<_>>();
+
+                        delegates.extend(peers.iter().cloned());
+                        delegations.push(Delegation::Indirect {
+                            urn: indirect.urn(),
+                            peers,
+                        });
+                        delegate_names.push(indirect.subject().name.to_string());
+                    }
+                }
+            }
+            let namespace = format!("{}{}/refs/", NAMESPACES, id);
+            let commits = match head {
+                Some(head) => count_commits(repo, head, previous)?,
+                None => 0,
+            };
+            let branches = repo
+                .references_glob(&format!("{}heads/*", namespace))?
+                .count();
+            let created = {
+                let mut walk = repo.revwalk()?;
+                walk.set_sorting(git2::Sort::TIME | git2::Sort::REVERSE)?;
+                walk.push_ref(&format!("{}rad/id", namespace))?;
+
+                match walk.next() {
+                    Some(oid) => repo.find_commit(oid?)?.time().seconds(),
+                    None => 0,
+                }
+            };
+            let updated = repo
+                .references_glob(&format!("{}*", namespace))?
+                .filter_map(|r| r.ok()?.peel_to_commit().ok())
+                .map(|c| c.time().seconds())
+                .max()
+                .unwrap_or(created);
+
+            Ok(Identity::Project {
+                project: Project {
+                    urn: doc.urn(),
+                    name: subject.name.to_string(),
+                    description: subject.description.as_ref().map(|d| d.to_string()),
+                    default_branch,
+                    delegates,
+                    delegations,
+                    head,
+                    created,
+                    updated,
+                    commits,
+                    branches,
+                    tracked: tracked(storage, &urn),
+                },
+                delegate_names,
+            })
+        }
+        SomeIdentity::Person(doc) => Ok(Identity::Person(Person {
+            urn: doc.urn(),
+            name: doc.subject().name.to_string(),
+            delegates: doc
+                .delegations()
+                .iter()
+                .cloned()
+                .map(PeerId::from)
+                .collect(),
+        })),
+        _ => Ok(Identity::None),
+    }
+}
+
+/// Count the commits reachable from a head. If the head descends from a previously counted
+/// one, only the new commits are walked.
+fn count_commits(
+    repo: &git2::Repository,
+    head: git2::Oid,
+    previous: Option<(git2::Oid, usize)>,
+) -> Result<usize, Error> {
+    let mut walk = repo.revwalk()?;
+    let mut count = 0;
+
+    walk.push(head)?;
+    if let Some((previous, commits)) = previous {
+        if previous == head {
+            return Ok(commits);
+        }
+        if repo.graph_descendant_of(head, previous)? {
+            walk.hide(previous)?;
+            count = commits;
+        }
+    }
+    Ok(count + walk.count())
+}
+
+/// Peers tracked in a namespace, in a stable order.
+fn tracked(storage: &ReadOnly, urn: &Urn) -> Vec<PeerId> {
+    let mut peers = tracking::tracked(storage, Some(urn))
+        .map(|tracked| {
+            tracked
+                .filter_map(|t| t.ok()?.peer_id())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    peers.sort_by_key(|peer| peer.to_string());
+    peers.dedup();
+    peers
+}
@@ -0,0 +1,280 @@
+//! Policy deciding which projects a seed serves.
+//!
+//! A policy file holds one rule per line, eg.
+//!
+//! ```text
+//! # Serve our org's projects, and projects of a trusted peer.
+//! default deny
+//! allow org 0x4a9cf21a805f1b2b6d3e9a5a3e6d1c0b3b2f2d1e
+//! allow delegate hynkyndc6w3p8urucakobzna7sxwgcqny7xxtw88dtx3pkf7m3nrzc
+//! deny urn rad:git:hnrkbtw9t1of4ykjy6er4qqwxtc54k9943eto
+//! read rad:git:hnrkqdpm9ub19oc8dccx44echy76hzfsezyio hyd7wpd8p6aex3ugfiu7p6kxz9ebdq8t4ykmewjnzjnsnb8zorhz8u
+//! ```
+//!
+//! A project is denied if any `deny` rule matches it, otherwise it's allowed if any `allow`
+//! rule matches it. Projects no rule matches get the default, which is `allow` unless set.
+//!
+//! Projects with `read` rules are private: they are only served to the peers listed, which
+//! may be given on one line or over several, and to the project delegates.
+//!
+//! Org rules match the projects anchored by an org, as recorded by the org node in the
+//! [`ANCHORS_FILE`] of the monorepo.
+//!
+//! Delegate rules need the project identity, so they don't apply to projects that aren't
+//! indexed yet, eg. on the first push of a project to the git-server. With `default deny`, a
+//! new project must be allowed by URN or org to be pushed, and a denied delegate can push a
+//! new project, which is only left out once it is indexed.
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::str::FromStr;
+use std::{fs, io};
+
+use librad::git::Urn;
+use librad::PeerId;
+
+/// File in the monorepo listing the projects anchored by each org, as `<org> <urn>` lines.
+pub const ANCHORS_FILE: &str = "org-anchors";
+
+/// Whether to serve a project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Allow,
+    Deny,
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(Self::Allow),
+            "deny" => Ok(Self::Deny),
+            other => Err(format!("unknown action '{}'", other)),
+        }
+    }
+}
+
+/// What a rule matches.
+#[derive(Debug, Clone)]
+pub enum Subject {
+    /// A project, by URN.
+    Urn(Urn),
+    /// Projects a peer is a delegate of, directly or on behalf of a person.
+    Delegate(PeerId),
+    /// Projects anchored by an org, by address.
+    Org(String),
+}
+
+/// A policy rule.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub action: Action,
+    pub subject: Subject,
+}
+
+/// A set of rules.
+#[derive(Debug, Clone)]
+pub struct Policy {
+    pub rules: Vec<Rule>,
+    pub default: Action,
+    /// Peers allowed to read each private project.
+    pub readers: HashMap<Urn, HashSet<PeerId>>,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            default: Action::Allow,
+            readers: HashMap::new(),
+        }
+    }
+}
+
+impl Policy {
+    /// Load a policy file.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let mut policy = Self::default();
+
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |msg: String| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}:{}: {}", path.display(), i + 1, msg),
+                )
+            };
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+
+            match fields.as_slice() {
+                ["default", action] => {
+                    policy.default = action.parse().map_err(invalid)?;
+                }
+                ["read", urn, peers @ ..] if !peers.is_empty() => {
+                    let urn = Urn::from_str(urn).map_err(|e| invalid(e.to_string()))?;
+                    let readers = policy.readers.entry(urn).or_default();
+
+                    for peer in peers {
+                        readers.insert(PeerId::from_str(peer).map_err(|e| invalid(e.to_string()))?);
+                    }
+                }
+                [action, kind, value] => {
+                    let action = action.parse().map_err(invalid)?;
+                    let subject = match *kind {
+                        "urn" => {
+                            Subject::Urn(Urn::from_str(value).map_err(|e| invalid(e.to_string()))?)
+                        }
+                        "delegate" => Subject::Delegate(
+                            PeerId::from_str(value).map_err(|e| invalid(e.to_string()))?,
+                        ),
+                        "org" => Subject::Org(value.to_lowercase()),
+                        other => return Err(invalid(format!("unknown rule type '{}'", other))),
+                    };
+                    policy.rules.push(Rule { action, subject });
+                }
+                _ => return Err(invalid(String::from("expected '<action> <type> <value>'"))),
+            }
+        }
+        Ok(policy)
+    }
+
+    /// Decide whether to serve a project, given its delegates and the orgs that anchored it.
+    pub fn allows(&self, urn: &Urn, delegates: &[PeerId], orgs: &HashSet<String>) -> bool {
+        let matching = self
+            .rules
+            .iter()
+            .filter(|rule| match &rule.subject {
+                Subject::Urn(u) => u == urn,
+                Subject::Delegate(peer) => delegates.contains(peer),
+                Subject::Org(org) => orgs.contains(org),
+            })
+            .map(|rule| rule.action)
+            .collect::<Vec<_>>();
+
+        if matching.contains(&Action::Deny) {
+            false
+        } else if matching.contains(&Action::Allow) {
+            true
+        } else {
+            self.default == Action::Allow
+        }
+    }
+
+    /// Whether a project is private.
+    pub fn is_private(&self, urn: &Urn) -> bool {
+        self.readers.contains_key(urn)
+    }
+
+    /// Whether a project may be read by the given peer, or by unauthenticated readers if no
+    /// peer is given. Delegates can always read their projects.
+    pub fn can_read(&self, urn: &Urn, delegates: &[PeerId], reader: Option<&PeerId>) -> bool {
+        match self.readers.get(urn) {
+            Some(readers) => {
+                reader.is_some_and(|peer| readers.contains(peer) || delegates.contains(peer))
+            }
+            None => true,
+        }
+    }
+}
+
+/// Load an anchors file, mapping project URNs to the orgs that anchored them. Lines that
+/// don't parse are skipped.
+pub fn load_anchors(path: &Path) -> io::Result<HashMap<Urn, HashSet<String>>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e),
+    };
+    let mut anchors: HashMap<Urn, HashSet<String>> = HashMap::new();
+
+    for line in contents.lines() {
+        let mut fields = line.split_whitespace();
+
+        if let (Some(org), Some(urn)) = (fields.next(), fields.next()) {
+            if let Ok(urn) = Urn::from_str(urn) {
+                anchors.entry(urn).or_default().insert(org.to_lowercase());
+            }
+        }
+    }
+    Ok(anchors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use librad::crypto::keystore::SecretKeyExt;
+    use librad::{SecStr, SecretKey};
+
+    const URN: &str = "rad:git:hnrkbtw9t1of4ykjy6er4qqwxtc54k9943eto";
+
+    fn peer(seed: u8) -> PeerId {
+        let key = SecretKey::from_bytes_and_meta(SecStr::new([seed; 32].to_vec()), &()).unwrap();
+
+        PeerId::from(key)
+    }
+
+    fn policy(default: Action, rules: Vec<Rule>) -> Policy {
+        Policy {
+            rules,
+            default,
+            readers: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_delegate_rules() {
+        let urn = Urn::from_str(URN).unwrap();
+        let (alice, bob) = (peer(1), peer(2));
+        let policy = policy(
+            Action::Deny,
+            vec![Rule {
+                action: Action::Allow,
+                subject: Subject::Delegate(alice),
+            }],
+        );
+
+        assert!(policy.allows(&urn, &[bob, alice], &HashSet::new()));
+        assert!(!policy.allows(&urn, &[bob], &HashSet::new()));
+    }
+
+    #[test]
+    fn test_unindexed_projects() {
+        let urn = Urn::from_str(URN).unwrap();
+        let alice = peer(1);
+        let orgs = vec![String::from("0xabc")].into_iter().collect();
+        let allow = policy(
+            Action::Deny,
+            vec![Rule {
+                action: Action::Allow,
+                subject: Subject::Delegate(alice),
+            }],
+        );
+        let deny = policy(
+            Action::Allow,
+            vec![Rule {
+                action: Action::Deny,
+                subject: Subject::Delegate(alice),
+            }],
+        );
+        let org = policy(
+            Action::Deny,
+            vec![Rule {
+                action: Action::Allow,
+                subject: Subject::Org(String::from("0xabc")),
+            }],
+        );
+
+        // Projects that aren't indexed have no known delegates: delegate rules don't match,
+        // and the default applies.
+        assert!(!allow.allows(&urn, &[], &HashSet::new()));
+        assert!(deny.allows(&urn, &[], &HashSet::new()));
+        // Org rules only need the anchors.
+        assert!(org.allows(&urn, &[], &orgs));
+    }
+}
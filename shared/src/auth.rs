@@ -0,0 +1,219 @@
+//! Authentication of radicle peers to the seed services.
+//!
+//! A peer authenticates by signing a challenge with its ed25519 key. The challenge binds the
+//! seed's peer id and an expiry time, eg. `radicle-seed-auth:<seed>:<expiry>`, so that tokens
+//! can be verified by any of the seed's services, without them keeping track of the challenges
+//! they issued.
+//!
+//! Tokens are of the form `<peer>.<expiry>.<signature>`, where the signature is encoded as
+//! URL-safe base64, without padding.
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use librad::PeerId;
+
+/// Prefix of challenges, so that signatures can't be reused for anything else.
+const CHALLENGE_PREFIX: &str = "radicle-seed-auth";
+/// Maximum lifetime of a token, in seconds.
+pub const MAX_TOKEN_LIFETIME: u64 = 24 * 60 * 60;
+
+/// Authentication errors.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("malformed token")]
+    Malformed,
+
+    #[error("token expired")]
+    Expired,
+
+    #[error("token lifetime exceeds the maximum")]
+    Lifetime,
+
+    #[error("invalid token signature")]
+    Signature,
+}
+
+/// The challenge a peer signs to authenticate with the given seed, valid until `expiry`, in
+/// seconds since the epoch.
+pub fn challenge(seed: &PeerId, expiry: u64) -> String {
+    format!("{}:{}:{}", CHALLENGE_PREFIX, seed, expiry)
+}
+
+/// Expiry of a challenge issued now, in seconds since the epoch.
+pub fn expiry() -> u64 {
+    now() + MAX_TOKEN_LIFETIME
+}
+
+/// A signed challenge.
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub peer: PeerId,
+    pub expiry: u64,
+    pub signature: [u8; 64],
+}
+
+impl Token {
+    /// Verify the token was signed by its peer for the given seed and hasn't expired.
+    /// Returns the authenticated peer.
+    pub fn verify(&self, seed: &PeerId) -> Result<PeerId, Error> {
+        let now = now();
+
+        if self.expiry <= now {
+            return Err(Error::Expired);
+        }
+        if self.expiry > now + MAX_TOKEN_LIFETIME {
+            return Err(Error::Lifetime);
+        }
+        let key = ed25519_zebra::VerificationKey::try_from(self.peer.as_public_key().as_ref())
+            .map_err(|_| Error::Signature)?;
+
+        key.verify(
+            &ed25519_zebra::Signature::from(self.signature),
+            challenge(seed, self.expiry).as_bytes(),
+        )
+        .map_err(|_| Error::Signature)?;
+
+        Ok(self.peer)
+    }
+}
+
+impl FromStr for Token {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().splitn(3, '.');
+        let (peer, expiry, signature) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(peer), Some(expiry), Some(signature)) => (peer, expiry, signature),
+            _ => return Err(Error::Malformed),
+        };
+        let signature = base64::decode_config(signature, base64::URL_SAFE_NO_PAD)
+            .map_err(|_| Error::Malformed)?;
+
+        Ok(Self {
+            peer: peer.parse().map_err(|_| Error::Malformed)?,
+            expiry: expiry.parse().map_err(|_| Error::Malformed)?,
+            signature: signature.try_into().map_err(|_| Error::Malformed)?,
+        })
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}",
+            self.peer,
+            self.expiry,
+            base64::encode_config(self.signature, base64::URL_SAFE_NO_PAD)
+        )
+    }
+}
+
+/// Parse and verify a token for the given seed. Returns the authenticated peer.
+pub fn verify(token: &str, seed: &PeerId) -> Result<PeerId, Error> {
+    token.parse::<Token>()?.verify(seed)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is after the epoch")
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use librad::crypto::keystore::SecretKeyExt;
+    use librad::{SecStr, SecretKey};
+
+    /// A peer with a fixed key, and its signing key.
+    fn peer(seed: u8) -> (PeerId, ed25519_zebra::SigningKey) {
+        let bytes = [seed; 32];
+        let secret = SecretKey::from_bytes_and_meta(SecStr::new(bytes.to_vec()), &()).unwrap();
+
+        (PeerId::from(secret), ed25519_zebra::SigningKey::from(bytes))
+    }
+
+    fn token(key: &ed25519_zebra::SigningKey, peer: PeerId, seed: &PeerId, expiry: u64) -> Token {
+        let signature = key.sign(challenge(seed, expiry).as_bytes());
+
+        Token {
+            peer,
+            expiry,
+            signature: signature.into(),
+        }
+    }
+
+    #[test]
+    fn test_valid_token() {
+        let (seed, _) = peer(1);
+        let (alice, key) = peer(2);
+        let token = token(&key, alice, &seed, expiry());
+
+        assert_eq!(verify(&token.to_string(), &seed).unwrap(), alice);
+    }
+
+    #[test]
+    fn test_expired_token() {
+        let (seed, _) = peer(1);
+        let (alice, key) = peer(2);
+        let token = token(&key, alice, &seed, now() - 1);
+
+        assert!(matches!(token.verify(&seed), Err(Error::Expired)));
+    }
+
+    #[test]
+    fn test_token_lifetime() {
+        let (seed, _) = peer(1);
+        let (alice, key) = peer(2);
+        let token = token(&key, alice, &seed, expiry() + 60);
+
+        assert!(matches!(token.verify(&seed), Err(Error::Lifetime)));
+    }
+
+    #[test]
+    fn test_token_for_other_seed() {
+        let (seed, _) = peer(1);
+        let (other, _) = peer(3);
+        let (alice, key) = peer(2);
+        let token = token(&key, alice, &other, expiry());
+
+        assert!(matches!(token.verify(&seed), Err(Error::Signature)));
+    }
+
+    #[test]
+    fn test_token_signed_by_other_peer() {
+        let (seed, _) = peer(1);
+        let (alice, _) = peer(2);
+        let (_, key) = peer(3);
+        let token = token(&key, alice, &seed, expiry());
+
+        assert!(matches!(token.verify(&seed), Err(Error::Signature)));
+    }
+
+    #[test]
+    fn test_tampered_signature() {
+        let (seed, _) = peer(1);
+        let (alice, key) = peer(2);
+        let mut token = token(&key, alice, &seed, expiry());
+
+        token.signature[0] ^= 1;
+
+        assert!(matches!(
+            verify(&token.to_string(), &seed),
+            Err(Error::Signature)
+        ));
+    }
+
+    #[test]
+    fn test_malformed_token() {
+        let (seed, _) = peer(1);
+
+        assert!(matches!(verify("", &seed), Err(Error::Malformed)));
+        assert!(matches!(verify("a.b.c", &seed), Err(Error::Malformed)));
+    }
+}
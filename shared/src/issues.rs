@@ -0,0 +1,6 @@
+//! Issues, stored as graphs of signed commits under `refs/remotes/<peer>/cobs/issues/<id>`.
+
+/// Prefix of issue refs, relative to a peer's remote refs.
+pub const ISSUES_PREFIX: &str = "cobs/issues/";
+/// Name of the blob holding an issue change, at the root of the commit tree.
+pub const CHANGE_BLOB: &str = "change.json";
@@ -1,5 +1,8 @@
+pub mod auth;
 pub mod identity;
+pub mod issues;
 pub mod signer;
+pub mod ssh;
 
 mod logging;
 
@@ -0,0 +1,229 @@
+//! SSH signatures and key fingerprints of radicle peers.
+//!
+//! Git signs commits with SSH keys in the `SSHSIG` format, see `PROTOCOL.sshsig` in OpenSSH.
+//! Only ed25519 keys, which is what radicle peers use, can be verified.
+use std::convert::{TryFrom, TryInto};
+
+use librad::PeerId;
+use sha2::Digest;
+
+/// Armor of SSH signatures.
+pub const ARMOR: &str = "-----BEGIN SSH SIGNATURE-----";
+const ARMOR_END: &str = "-----END SSH SIGNATURE-----";
+const SSHSIG_MAGIC: &[u8] = b"SSHSIG";
+const SSH_ED25519: &str = "ssh-ed25519";
+/// Namespace git uses when signing commits.
+const GIT_NAMESPACE: &str = "git";
+
+/// Verification status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Valid,
+    Invalid,
+    /// The signature couldn't be verified, eg. because of an unsupported key type.
+    Unknown,
+}
+
+/// A parsed `SSHSIG` signature.
+#[derive(Debug, Clone)]
+pub struct Signature {
+    /// Public key blob.
+    public_key: Vec<u8>,
+    namespace: Vec<u8>,
+    reserved: Vec<u8>,
+    hash_algorithm: Vec<u8>,
+    /// Signature blob.
+    signature: Vec<u8>,
+}
+
+impl Signature {
+    /// Parse an armored signature, as found in the `gpgsig` header of a commit.
+    pub fn from_armored(armored: &str) -> Option<Self> {
+        let body = armored
+            .lines()
+            .map(|l| l.trim())
+            .skip_while(|l| *l != ARMOR)
+            .skip(1)
+            .take_while(|l| *l != ARMOR_END)
+            .collect::<String>();
+        let bytes = base64::decode(body).ok()?;
+        let mut reader = Reader(&bytes);
+
+        if reader.take(SSHSIG_MAGIC.len())? != SSHSIG_MAGIC {
+            return None;
+        }
+        if reader.u32()? != 1 {
+            return None;
+        }
+        Some(Self {
+            public_key: reader.string()?.to_vec(),
+            namespace: reader.string()?.to_vec(),
+            reserved: reader.string()?.to_vec(),
+            hash_algorithm: reader.string()?.to_vec(),
+            signature: reader.string()?.to_vec(),
+        })
+    }
+
+    /// Fingerprint of the signing key.
+    pub fn fingerprint(&self) -> String {
+        fingerprint(&self.public_key)
+    }
+
+    /// Verify the signature of a git commit, given the signed commit data.
+    pub fn verify(&self, message: &[u8]) -> Status {
+        let mut key = Reader(&self.public_key);
+        let mut sig = Reader(&self.signature);
+
+        let (key_type, key, sig_type, sig) =
+            match (key.string(), key.string(), sig.string(), sig.string()) {
+                (Some(kt), Some(k), Some(st), Some(s)) => (kt, k, st, s),
+                _ => return Status::Invalid,
+            };
+        if key_type != SSH_ED25519.as_bytes() || sig_type != SSH_ED25519.as_bytes() {
+            return Status::Unknown;
+        }
+        if self.namespace != GIT_NAMESPACE.as_bytes() {
+            return Status::Invalid;
+        }
+        let hash = match self.hash_algorithm.as_slice() {
+            b"sha512" => sha2::Sha512::digest(message).to_vec(),
+            b"sha256" => sha2::Sha256::digest(message).to_vec(),
+            _ => return Status::Unknown,
+        };
+
+        let mut signed = SSHSIG_MAGIC.to_vec();
+        put_string(&mut signed, &self.namespace);
+        put_string(&mut signed, &self.reserved);
+        put_string(&mut signed, &self.hash_algorithm);
+        put_string(&mut signed, &hash);
+
+        let key = match ed25519_zebra::VerificationKey::try_from(key) {
+            Ok(key) => key,
+            Err(_) => return Status::Invalid,
+        };
+        let sig: [u8; 64] = match sig.try_into() {
+            Ok(sig) => sig,
+            Err(_) => return Status::Invalid,
+        };
+        match key.verify(&ed25519_zebra::Signature::from(sig), &signed) {
+            Ok(()) => Status::Valid,
+            Err(_) => Status::Invalid,
+        }
+    }
+}
+
+/// SSH key fingerprint of a peer, as `ssh-keygen -l` would print it, eg. `SHA256:...`.
+pub fn peer_fingerprint(peer: &PeerId) -> String {
+    let key = peer.as_public_key();
+    let mut blob = Vec::new();
+
+    put_string(&mut blob, SSH_ED25519.as_bytes());
+    put_string(&mut blob, key.as_ref());
+
+    fingerprint(&blob)
+}
+
+/// SSH key fingerprint of a public key blob.
+fn fingerprint(blob: &[u8]) -> String {
+    format!(
+        "SHA256:{}",
+        base64::encode_config(sha2::Sha256::digest(blob), base64::STANDARD_NO_PAD)
+    )
+}
+
+/// Reader for the SSH wire format.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+
+        Some(head)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn string(&mut self) -> Option<&'a [u8]> {
+        let len = self.u32()? as usize;
+
+        self.take(len)
+    }
+}
+
+/// Write a length-prefixed string in the SSH wire format.
+fn put_string(buf: &mut Vec<u8>, s: &[u8]) {
+    buf.extend_from_slice(&(s.len() as u32).to_be_bytes());
+    buf.extend_from_slice(s);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use librad::crypto::keystore::SecretKeyExt;
+    use librad::{SecStr, SecretKey};
+
+    /// Seed of the ed25519 key the fixtures were made with, using `ssh-keygen -Y sign -n git`.
+    const SEED: [u8; 32] = [
+        250, 249, 12, 220, 92, 42, 237, 14, 141, 183, 25, 207, 83, 247, 241, 123, 19, 26, 231, 253,
+        3, 146, 37, 138, 113, 155, 171, 151, 26, 109, 71, 51,
+    ];
+    /// Key fingerprint, as printed by `ssh-keygen -l`.
+    const FINGERPRINT: &str = "SHA256:qM2Rfk7NuFzoEmhOjemlmBb1jm33JWCA5VJEThtK/X0";
+    const MESSAGE: &str = "tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n\
+        author Alice <alice@example.com> 1700000000 +0000\n\
+        committer Alice <alice@example.com> 1700000000 +0000\n\
+        \n\
+        Initial commit\n";
+    const SIGNATURE: &str = "-----BEGIN SSH SIGNATURE-----
+U1NIU0lHAAAAAQAAADMAAAALc3NoLWVkMjU1MTkAAAAgbejaW1xvC5pkOSnkzy3llPlCuy
+D3rFQuioDryjt40b0AAAADZ2l0AAAAAAAAAAZzaGE1MTIAAABTAAAAC3NzaC1lZDI1NTE5
+AAAAQFh2FHbhZ+3YAtelcH0into0ko3wyfVGPY2pBK7Ga7LkZhcIlhpGgiqFLPUsZ5KAU0
+iW6bXZI8ifBypNflmSpwE=
+-----END SSH SIGNATURE-----
+";
+
+    #[test]
+    fn test_verify() {
+        let sig = Signature::from_armored(SIGNATURE).unwrap();
+
+        assert_eq!(sig.fingerprint(), FINGERPRINT);
+        assert_eq!(sig.verify(MESSAGE.as_bytes()), Status::Valid);
+    }
+
+    #[test]
+    fn test_verify_tampered_message() {
+        let sig = Signature::from_armored(SIGNATURE).unwrap();
+        let message = MESSAGE.replace("Initial", "Final");
+
+        assert_eq!(sig.verify(message.as_bytes()), Status::Invalid);
+    }
+
+    #[test]
+    fn test_verify_other_namespace() {
+        let mut sig = Signature::from_armored(SIGNATURE).unwrap();
+        sig.namespace = b"file".to_vec();
+
+        assert_eq!(sig.verify(MESSAGE.as_bytes()), Status::Invalid);
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert!(Signature::from_armored("").is_none());
+        assert!(Signature::from_armored(&SIGNATURE.replace("U1NIU0lH", "U1NIU0lI")).is_none());
+    }
+
+    #[test]
+    fn test_peer_fingerprint() {
+        let key = SecretKey::from_bytes_and_meta(SecStr::new(SEED.to_vec()), &()).unwrap();
+
+        assert_eq!(peer_fingerprint(&PeerId::from(key)), FINGERPRINT);
+    }
+}